@@ -1,6 +1,6 @@
 use crate::forward_ref_binop;
 use std::fmt;
-use std::ops::{Add, Div, Mul, Sub};
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct FieldElement {
@@ -14,7 +14,74 @@ impl fmt::Display for FieldElement {
     }
 }
 
+// Formats just the number, so {:x} and {:#x} behave as they do for integers
+impl fmt::LowerHex for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.num, f)
+    }
+}
+
+// With the debug-checks feature, intermediate products are checked for overflow
+#[cfg(feature = "debug-checks")]
+fn mul_num(a: i64, b: i64) -> i64 {
+    a.checked_mul(b)
+        .unwrap_or_else(|| panic!("Overflow while multiplying {} by {}", a, b))
+}
+
+#[cfg(not(feature = "debug-checks"))]
+fn mul_num(a: i64, b: i64) -> i64 {
+    a * b
+}
+
+// Exponentiation by squaring, returning base^exp mod modulus in 0..modulus
+pub fn modpow(base: i64, mut exp: u64, modulus: i64) -> i64 {
+    if modulus == 1 {
+        return 0;
+    }
+    // Intermediate products of two values below modulus always fit in i128
+    let modulus = modulus as i128;
+    let mut base = (base as i128).rem_euclid(modulus);
+    let mut result: i128 = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        exp >>= 1;
+        base = base * base % modulus;
+    }
+    result as i64
+}
+
+// Inverts every element with a single field inversion (Montgomery's trick)
+pub fn batch_inverse(elements: &[FieldElement]) -> Vec<FieldElement> {
+    if elements.is_empty() {
+        return Vec::new();
+    }
+    if let Some(zero) = elements.iter().find(|e| e.num == 0) {
+        panic!("Cannot invert zero in {}", zero);
+    }
+
+    // prefix[i] is the product of elements[0..=i]
+    let mut prefix = Vec::with_capacity(elements.len());
+    let mut acc = elements[0];
+    prefix.push(acc);
+    for e in &elements[1..] {
+        acc = acc * e;
+        prefix.push(acc);
+    }
+
+    let mut inv = acc.pow(-1);
+    let mut result = vec![inv; elements.len()];
+    for i in (1..elements.len()).rev() {
+        result[i] = inv * prefix[i - 1];
+        inv = inv * elements[i];
+    }
+    result[0] = inv;
+    result
+}
+
 impl FieldElement {
+    // Strict: panics unless 0 <= num < prime
     pub fn new(num: i64, prime: i64) -> Self {
         if num >= prime || num < 0 {
             panic!("Num {} not in field range 0 to {}", num, prime);
@@ -23,25 +90,69 @@ impl FieldElement {
         FieldElement { num, prime }
     }
 
+    pub fn to_hex(&self) -> String {
+        format!("{:x}", self)
+    }
+
+    // Every element of the field, in order of num
+    pub fn elements(prime: i64) -> impl Iterator<Item = FieldElement> {
+        (0..prime).map(move |num| FieldElement::new(num, prime))
+    }
+
+    // Lenient: reduces any num, including negative ones, into the field first
+    pub fn from_reduced(num: i64, prime: i64) -> Self {
+        FieldElement::new(num.rem_euclid(prime), prime)
+    }
+
+    // Exponents are reduced mod p - 1 by Fermat's little theorem, which does not hold for zero:
+    // 0^0 is 1 by convention, 0^n is 0 for positive n, and negative powers of zero panic.
+    // For prime 2 every exponent reduces to 0, which is still right as 1 is the only non-zero element.
     pub fn pow(&self, num: i64) -> Self {
-        let mod_pow = |mut base: i64, mut exp: i64, modulus: i64| {
-            if modulus == 1 {
-                return 0;
-            }
-            let mut result: i64 = 1;
-            base = base % modulus;
-            while exp > 0 {
-                if exp % 2 == 1 {
-                    result = result * base % modulus;
-                }
-                exp = exp >> 1;
-                base = base * base % modulus
+        if self.prime < 2 {
+            panic!(
+                "Cannot raise {} to a power: {} is not a prime",
+                self, self.prime
+            );
+        }
+        if self.num == 0 {
+            if num < 0 {
+                panic!("Cannot raise zero to a negative power in {}", self);
             }
-            result
-        };
+            let new_num = if num == 0 { 1 } else { 0 };
+            return FieldElement::new(new_num, self.prime);
+        }
+
         let n = num.rem_euclid(self.prime - 1);
-        let new_num = mod_pow(self.num, n, self.prime).rem_euclid(self.prime);
-        FieldElement::new(new_num, self.prime)
+        FieldElement::new(modpow(self.num, n as u64, self.prime), self.prime)
+    }
+
+    pub fn square(&self) -> Self {
+        self * self
+    }
+
+    // Division when the divisor's inverse is already known, e.g. from batch_inverse
+    pub fn div_by(&self, inv_of_divisor: &FieldElement) -> Self {
+        self * inv_of_divisor
+    }
+
+    // Euler's criterion: a non-zero a is a square exactly when a^((p-1)/2) == 1
+    pub fn is_quadratic_residue(&self) -> bool {
+        self.num == 0 || self.pow((self.prime - 1) / 2).num == 1
+    }
+
+    // self - other, or None when the two are equal and the difference would be zero
+    pub fn sub_nonzero(&self, other: &FieldElement) -> Option<FieldElement> {
+        let difference = self - other;
+        if difference.num == 0 {
+            None
+        } else {
+            Some(difference)
+        }
+    }
+
+    // An integer as an element of this element's field, e.g. the 3 in 3 * x
+    fn lift(&self, n: i64) -> FieldElement {
+        FieldElement::new(n.rem_euclid(self.prime), self.prime)
     }
 }
 
@@ -53,8 +164,9 @@ impl Add for FieldElement {
             panic!("Cannot add two numbers in different Fields");
         }
 
-        let new_num = (self.num + other.num).rem_euclid(self.prime);
-        FieldElement::new(new_num, self.prime)
+        // Widen so the sum of two elements near an i64-sized prime cannot overflow
+        let new_num = (self.num as i128 + other.num as i128).rem_euclid(self.prime as i128);
+        FieldElement::new(new_num as i64, self.prime)
     }
 }
 forward_ref_binop! { impl Add, add for FieldElement }
@@ -67,8 +179,8 @@ impl Sub for FieldElement {
             panic!("Cannot add two numbers in different Fields");
         }
 
-        let new_other = FieldElement::new((-1 * other.num).rem_euclid(self.prime), self.prime);
-        self + new_other
+        let new_num = (self.num as i128 - other.num as i128).rem_euclid(self.prime as i128);
+        FieldElement::new(new_num as i64, self.prime)
     }
 }
 forward_ref_binop! { impl Sub, sub for FieldElement }
@@ -81,7 +193,10 @@ impl Mul for FieldElement {
             panic!("Cannot add two numbers in different Fields");
         }
 
-        FieldElement::new((self.num * other.num).rem_euclid(self.prime), self.prime)
+        FieldElement::new(
+            mul_num(self.num, other.num).rem_euclid(self.prime),
+            self.prime,
+        )
     }
 }
 forward_ref_binop! { impl Mul, mul for FieldElement }
@@ -99,6 +214,50 @@ impl Div for FieldElement {
 }
 forward_ref_binop! { impl Div, div for FieldElement }
 
+impl Neg for FieldElement {
+    type Output = FieldElement;
+
+    fn neg(self) -> FieldElement {
+        FieldElement::new((self.prime - self.num) % self.prime, self.prime)
+    }
+}
+
+impl Add<i64> for FieldElement {
+    type Output = FieldElement;
+
+    fn add(self, other: i64) -> FieldElement {
+        self + self.lift(other)
+    }
+}
+forward_ref_binop! { impl Add, add for FieldElement, i64 }
+
+impl Add<FieldElement> for i64 {
+    type Output = FieldElement;
+
+    fn add(self, other: FieldElement) -> FieldElement {
+        other + self
+    }
+}
+forward_ref_binop! { impl Add, add for i64, FieldElement }
+
+impl Mul<i64> for FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, other: i64) -> FieldElement {
+        self * self.lift(other)
+    }
+}
+forward_ref_binop! { impl Mul, mul for FieldElement, i64 }
+
+impl Mul<FieldElement> for i64 {
+    type Output = FieldElement;
+
+    fn mul(self, other: FieldElement) -> FieldElement {
+        other * self
+    }
+}
+forward_ref_binop! { impl Mul, mul for i64, FieldElement }
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,6 +275,44 @@ mod tests {
         assert_ne!(a, b);
     }
 
+    #[test]
+    fn from_reduced_test() {
+        let prime = 13;
+        assert_eq!(FieldElement::from_reduced(prime + 3, prime).num, 3);
+        assert_eq!(FieldElement::from_reduced(-1, prime).num, 12);
+        assert_eq!(
+            FieldElement::from_reduced(7, prime),
+            FieldElement::new(7, prime)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not in field range")]
+    fn new_out_of_range_test() {
+        FieldElement::new(13, 13);
+    }
+
+    #[test]
+    fn elements_test() {
+        let elements: Vec<FieldElement> = FieldElement::elements(13).collect();
+        assert_eq!(elements.len(), 13);
+        for (i, e) in elements.iter().enumerate() {
+            assert_eq!(e.num, i as i64);
+            assert_eq!(e.prime, 13);
+        }
+    }
+
+    #[test]
+    fn lower_hex_test() {
+        let prime = 9223372036854775783;
+        let a = FieldElement::new(0x7fff_ffff_ffff_ff00, prime);
+        assert_eq!(format!("{:x}", a), "7fffffffffffff00");
+        assert_eq!(format!("{:#x}", a), "0x7fffffffffffff00");
+        assert_eq!(a.to_hex(), "7fffffffffffff00");
+        assert_eq!(FieldElement::new(10, 13).to_hex(), "a");
+        assert_eq!(a.to_string(), format!("FieldElement_{}({})", prime, a.num));
+    }
+
     #[test]
     fn add_test() {
         let a = FieldElement::new(7, 13);
@@ -132,6 +329,23 @@ mod tests {
         assert_eq!(&a - &b, c);
     }
 
+    #[test]
+    fn add_large_prime_test() {
+        // The largest prime below 2^63
+        let prime = 9223372036854775783;
+        let a = FieldElement::new(i64::MAX / 2 + 1, prime);
+        assert_eq!(a + a, FieldElement::new(25, prime));
+    }
+
+    #[test]
+    fn sub_large_prime_test() {
+        let prime = 9223372036854775783;
+        let a = FieldElement::new(1, prime);
+        let b = FieldElement::new(prime - 1, prime);
+        assert_eq!(a - b, FieldElement::new(2, prime));
+        assert_eq!(b - a, FieldElement::new(prime - 2, prime));
+    }
+
     #[test]
     fn mul_test() {
         let a = FieldElement::new(8, 19);
@@ -140,6 +354,31 @@ mod tests {
         assert_eq!(&a * &b, c);
     }
 
+    #[test]
+    fn neg_test() {
+        let a = FieldElement::new(8, 19);
+        assert_eq!(-a, FieldElement::new(11, 19));
+        assert_eq!(a + -a, FieldElement::new(0, 19));
+        assert_eq!(-FieldElement::new(0, 19), FieldElement::new(0, 19));
+    }
+
+    #[test]
+    fn mul_i64_test() {
+        let a = FieldElement::new(8, 19);
+        assert_eq!(a * 3, 3 * a);
+        assert_eq!(a * 3, FieldElement::new(5, 19));
+        assert_eq!(a * -1, FieldElement::new(11, 19));
+        assert_eq!(a * 22, a * 3);
+    }
+
+    #[test]
+    fn add_i64_test() {
+        let a = FieldElement::new(8, 19);
+        assert_eq!(a + 13, 13 + a);
+        assert_eq!(a + 13, FieldElement::new(2, 19));
+        assert_eq!(a + -9, FieldElement::new(18, 19));
+    }
+
     #[test]
     fn pow_test() {
         let a = FieldElement::new(3, 13);
@@ -155,6 +394,124 @@ mod tests {
         assert_eq!(&a / &b, c);
     }
 
+    #[test]
+    fn zero_pow_test() {
+        let zero = FieldElement::new(0, 5);
+        assert_eq!(zero.pow(0), FieldElement::new(1, 5));
+        assert_eq!(zero.pow(5), zero);
+        // A multiple of p - 1 must not be reduced to 0^0
+        assert_eq!(zero.pow(4), zero);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot raise zero to a negative power")]
+    fn zero_negative_pow_test() {
+        FieldElement::new(0, 5).pow(-1);
+    }
+
+    #[test]
+    fn batch_inverse_test() {
+        let prime = 223;
+        let elements: Vec<FieldElement> = (1..prime).map(|n| FieldElement::new(n, prime)).collect();
+        let inverses = batch_inverse(&elements);
+        for (e, inv) in elements.iter().zip(inverses.iter()) {
+            assert_eq!(*inv, e.pow(-1));
+        }
+        assert!(batch_inverse(&[]).is_empty());
+    }
+
+    #[test]
+    fn div_by_test() {
+        let prime = 19;
+        let a = FieldElement::new(2, prime);
+        let b = FieldElement::new(7, prime);
+        assert_eq!(a.div_by(&batch_inverse(&[b])[0]), a / b);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot invert zero")]
+    fn batch_inverse_zero_test() {
+        batch_inverse(&[FieldElement::new(3, 7), FieldElement::new(0, 7)]);
+    }
+
+    #[test]
+    fn pow_prime_2_test() {
+        let one = FieldElement::new(1, 2);
+        let zero = FieldElement::new(0, 2);
+        assert_eq!(one.pow(1), one);
+        assert_eq!(one.pow(0), one);
+        assert_eq!(one.pow(-5), one);
+        assert_eq!(zero.pow(1), zero);
+        assert_eq!(zero.pow(2), zero);
+    }
+
+    #[test]
+    fn pow_prime_3_test() {
+        let two = FieldElement::new(2, 3);
+        assert_eq!(two.pow(1), two);
+        assert_eq!(two.pow(2), FieldElement::new(1, 3));
+        assert_eq!(two.pow(3), two);
+        assert_eq!(two.pow(-1), two);
+        assert_eq!(FieldElement::new(0, 3).pow(2), FieldElement::new(0, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a prime")]
+    fn pow_prime_1_test() {
+        FieldElement::new(0, 1).pow(1);
+    }
+
+    #[test]
+    fn sub_nonzero_test() {
+        let prime = 31;
+        let a = FieldElement::new(29, prime);
+        let b = FieldElement::new(4, prime);
+        assert_eq!(a.sub_nonzero(&b), Some(FieldElement::new(25, prime)));
+        assert_eq!(b.sub_nonzero(&a), Some(FieldElement::new(6, prime)));
+        assert_eq!(a.sub_nonzero(&a), None);
+    }
+
+    #[test]
+    fn square_test() {
+        let prime = 223;
+        for num in 0..prime {
+            let a = FieldElement::new(num, prime);
+            assert_eq!(a.square(), a.pow(2));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "debug-checks")]
+    #[should_panic(expected = "Overflow while multiplying")]
+    fn mul_overflow_test() {
+        let prime = 4294967311;
+        let a = FieldElement::new(prime - 1, prime);
+        let _ = a * a;
+    }
+
+    #[test]
+    fn is_quadratic_residue_test() {
+        let prime = 223;
+        for num in 0..prime {
+            let a = FieldElement::new(num, prime);
+            let has_root = (0..prime).any(|x| FieldElement::new(x, prime).square() == a);
+            assert_eq!(a.is_quadratic_residue(), has_root);
+        }
+    }
+
+    #[test]
+    fn modpow_test() {
+        assert_eq!(modpow(4, 13, 497), 445);
+        assert_eq!(modpow(3, 200, 13), 9);
+        assert_eq!(modpow(-2, 3, 7), 6);
+        assert_eq!(modpow(2, 0, 7), 1);
+        assert_eq!(modpow(0, 0, 7), 1);
+        assert_eq!(modpow(5, 3, 1), 0);
+        let prime = 9223372036854775783;
+        assert_eq!(modpow(prime - 1, 2, prime), 1);
+        assert_eq!(modpow(2, 63, prime), 25);
+    }
+
     #[test]
     fn pow_test2() {
         let a = FieldElement::new(17, 31);
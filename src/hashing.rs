@@ -0,0 +1,71 @@
+use ripemd::{Digest, Ripemd160};
+use sha1::Sha1;
+
+#[cfg(not(any(feature = "sha2", feature = "pure-rust-hashes")))]
+compile_error!("either the sha2 or the pure-rust-hashes feature must be enabled");
+
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    Sha1::digest(data).into()
+}
+
+#[cfg(not(feature = "pure-rust-hashes"))]
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    sha2::Sha256::digest(data).into()
+}
+
+// The in-crate implementation, for builds that avoid the sha2 crate
+#[cfg(feature = "pure-rust-hashes")]
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    crate::sha256::digest(data)
+}
+
+pub fn ripemd160(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(data).into()
+}
+
+// sha256 applied twice
+pub fn hash256(data: &[u8]) -> [u8; 32] {
+    sha256(&sha256(data))
+}
+
+// sha256 followed by ripemd160
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    ripemd160(&sha256(data))
+}
+
+#[cfg(test)]
+pub(crate) fn decode_hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_test() {
+        assert_eq!(
+            sha256(b"abc").to_vec(),
+            decode_hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+    }
+
+    #[test]
+    fn hash256_test() {
+        assert_eq!(
+            hash256(b"abc").to_vec(),
+            decode_hex("4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358")
+        );
+    }
+
+    #[test]
+    fn hash160_test() {
+        assert_eq!(
+            hash160(b"abc").to_vec(),
+            decode_hex("bb1be98c142444d7a56aa3981c3942a978e4dc33")
+        );
+    }
+}
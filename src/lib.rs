@@ -1,5 +1,15 @@
 pub mod field_element;
+pub mod hashing;
+pub mod merkle;
+pub mod network;
+pub mod op;
 pub mod point;
 pub mod s256_field;
+pub mod script;
+pub mod secp256k1;
+pub mod util;
+pub mod varint;
 
 mod internal_macros;
+#[cfg(any(test, feature = "pure-rust-hashes"))]
+mod sha256;
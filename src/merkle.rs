@@ -0,0 +1,215 @@
+use crate::hashing::hash256;
+
+pub fn merkle_parent(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left);
+    data[32..].copy_from_slice(right);
+    hash256(&data)
+}
+
+// Pairs up the hashes, repeating the last one when there is an odd number of them
+pub fn merkle_parent_level(hashes: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    hashes
+        .chunks(2)
+        .map(|pair| merkle_parent(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+pub fn merkle_root(hashes: &[[u8; 32]]) -> [u8; 32] {
+    if hashes.is_empty() {
+        panic!("Cannot compute the merkle root of no hashes");
+    }
+
+    let mut current = hashes.to_vec();
+    while current.len() > 1 {
+        current = merkle_parent_level(&current);
+    }
+    current[0]
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerkleTree {
+    // Every level from the leaves up to the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: &[[u8; 32]]) -> Self {
+        if leaves.is_empty() {
+            panic!("Cannot build a merkle tree with no leaves");
+        }
+
+        let mut levels = vec![leaves.to_vec()];
+        while levels[levels.len() - 1].len() > 1 {
+            let parents = merkle_parent_level(&levels[levels.len() - 1]);
+            levels.push(parents);
+        }
+        MerkleTree { levels }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    // Sibling hashes from the leaf up to the root, each flagged true when the sibling is on the right
+    pub fn proof(&self, txid: &[u8; 32]) -> Option<Vec<([u8; 32], bool)>> {
+        let mut index = self.levels[0].iter().position(|leaf| leaf == txid)?;
+        let mut path = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = if index % 2 == 0 {
+                (*level.get(index + 1).unwrap_or(&level[index]), true)
+            } else {
+                (level[index - 1], false)
+            };
+            path.push(sibling);
+            index /= 2;
+        }
+        Some(path)
+    }
+}
+
+pub fn verify_proof(root: &[u8; 32], txid: &[u8; 32], proof: &[([u8; 32], bool)]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(*txid, |current, (sibling, sibling_is_right)| {
+            if *sibling_is_right {
+                merkle_parent(&current, sibling)
+            } else {
+                merkle_parent(sibling, &current)
+            }
+        });
+    &computed == root
+}
+
+// Computes the same root as merkle_root while keeping at most one pending hash per level
+#[derive(Clone, Debug, Default)]
+pub struct MerkleRootBuilder {
+    // levels[i] is a finished subtree of 2^i leaves still waiting for its right sibling
+    levels: Vec<Option<[u8; 32]>>,
+}
+
+impl MerkleRootBuilder {
+    pub fn new() -> Self {
+        MerkleRootBuilder { levels: Vec::new() }
+    }
+
+    pub fn push(&mut self, txid: [u8; 32]) {
+        let mut current = txid;
+        for level in self.levels.iter_mut() {
+            match level.take() {
+                Some(left) => current = merkle_parent(&left, &current),
+                None => {
+                    *level = Some(current);
+                    return;
+                }
+            }
+        }
+        self.levels.push(Some(current));
+    }
+
+    pub fn finalize(self) -> [u8; 32] {
+        let top = match self.levels.len() {
+            0 => panic!("Cannot compute the merkle root of no hashes"),
+            len => len - 1,
+        };
+
+        // Walk up the right edge, duplicating any hash left without a sibling
+        let mut carry: Option<[u8; 32]> = None;
+        for (i, level) in self.levels.iter().enumerate() {
+            carry = match (*level, carry) {
+                (Some(left), Some(right)) => Some(merkle_parent(&left, &right)),
+                (Some(hash), None) if i == top => return hash,
+                (Some(hash), None) | (None, Some(hash)) => Some(merkle_parent(&hash, &hash)),
+                (None, None) => None,
+            };
+        }
+        carry.expect("the top level always holds a hash")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::decode_hex;
+    use std::convert::TryInto;
+
+    fn hash(hex: &str) -> [u8; 32] {
+        decode_hex(hex).try_into().unwrap()
+    }
+
+    fn leaves(count: u8) -> Vec<[u8; 32]> {
+        (0..count).map(|i| hash256(&[i])).collect()
+    }
+
+    #[test]
+    fn merkle_root_test() {
+        let hashes: Vec<[u8; 32]> = [
+            "c117ea8ec828342f4dfb0ad6bd140e03a50720ece40169ee38bdc15d9eb64cf5",
+            "c131474164b412e3406696da1ee20ab0fc9bf41c8f05fa8ceea7a08d672d7cc5",
+            "f391da6ecfeed1814efae39e7fcb3838ae0b02c02ae7d0a5848a66947c0727b0",
+            "3d238a92a94532b946c90e19c49351c763696cff3db400485b813aecb8a13181",
+            "10092f2633be5f3ce349bf9ddbde36caa3dd10dfa0ec8106bce23acbff637dae",
+            "7d37b3d54fa6a64869084bfd2e831309118b9e833610e6228adacdbd1b4ba161",
+            "8118a77e542892fe15ae3fc771a4abfd2f5d5d5997544c3487ac36b5c85170fc",
+            "dff6879848c2c9b62fe652720b8df5272093acfaa45a43cdb3696fe2466a3877",
+            "b825c0745f46ac58f7d3759e6dc535a1fec7820377f24d4c2c6ad2cc55c0cb59",
+            "95513952a04bd8992721e9b7e2937f1c04ba31e0469fbe615a78197f68f52b7c",
+            "2e6d722e5e4dbdf2447ddecc9f7dabb8e299bae921c99ad5b0184cd9eb8e5908",
+            "b13a750047bc0bdceb2473e5fe488c2596d7a7124b4e716fdd29b046ef99bbf0",
+        ]
+        .iter()
+        .map(|h| hash(h))
+        .collect();
+        let want = hash("acbcab8bcc1af95d8d563b77d24c3d19b18f1486383d75a5085c4e86c86beed6");
+        assert_eq!(merkle_root(&hashes), want);
+        assert_eq!(MerkleTree::new(&hashes).root(), want);
+    }
+
+    #[test]
+    fn merkle_root_builder_test() {
+        for count in 1..=20 {
+            let hashes = leaves(count);
+            let mut builder = MerkleRootBuilder::new();
+            for hash in &hashes {
+                builder.push(*hash);
+            }
+            assert_eq!(builder.finalize(), merkle_root(&hashes), "count {}", count);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot compute the merkle root of no hashes")]
+    fn empty_merkle_root_builder_test() {
+        MerkleRootBuilder::new().finalize();
+    }
+
+    #[test]
+    fn proof_test() {
+        let hashes = leaves(5);
+        let tree = MerkleTree::new(&hashes);
+        let root = merkle_root(&hashes);
+        for leaf in &hashes {
+            let proof = tree.proof(leaf).unwrap();
+            assert_eq!(proof.len(), 3);
+            assert!(verify_proof(&root, leaf, &proof));
+        }
+    }
+
+    #[test]
+    fn proof_rejects_wrong_leaf_test() {
+        let hashes = leaves(4);
+        let tree = MerkleTree::new(&hashes);
+        let proof = tree.proof(&hashes[1]).unwrap();
+        assert!(!verify_proof(&tree.root(), &hashes[2], &proof));
+        assert_eq!(tree.proof(&hash256(b"missing")), None);
+    }
+
+    #[test]
+    fn single_leaf_proof_test() {
+        let hashes = leaves(1);
+        let tree = MerkleTree::new(&hashes);
+        let proof = tree.proof(&hashes[0]).unwrap();
+        assert!(proof.is_empty());
+        assert!(verify_proof(&tree.root(), &hashes[0], &proof));
+    }
+}
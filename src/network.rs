@@ -0,0 +1,485 @@
+use crate::util::{read_array, read_u32_le, read_u64_le};
+use crate::varint;
+use std::io::{self, Read};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl Network {
+    pub fn p2pkh_prefix(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x00,
+            Network::Testnet | Network::Regtest => 0x6f,
+        }
+    }
+
+    pub fn p2sh_prefix(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x05,
+            Network::Testnet | Network::Regtest => 0xc4,
+        }
+    }
+
+    pub fn wif_prefix(&self) -> u8 {
+        match self {
+            Network::Mainnet => 0x80,
+            Network::Testnet | Network::Regtest => 0xef,
+        }
+    }
+
+    pub fn bech32_hrp(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "bc",
+            Network::Testnet => "tb",
+            Network::Regtest => "bcrt",
+        }
+    }
+
+    // The magic bytes that start every p2p message envelope
+    pub fn magic(&self) -> [u8; 4] {
+        match self {
+            Network::Mainnet => [0xf9, 0xbe, 0xb4, 0xd9],
+            Network::Testnet => [0x0b, 0x11, 0x09, 0x07],
+            Network::Regtest => [0xfa, 0xbf, 0xb5, 0xda],
+        }
+    }
+
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Network::Mainnet => 8333,
+            Network::Testnet => 18333,
+            Network::Regtest => 18444,
+        }
+    }
+}
+
+impl From<bool> for Network {
+    // Maps the `testnet` flag used throughout the book to a network
+    fn from(testnet: bool) -> Self {
+        if testnet {
+            Network::Testnet
+        } else {
+            Network::Mainnet
+        }
+    }
+}
+
+// The network address structure embedded in version and addr messages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NetAddr {
+    pub services: u64,
+    pub ip: [u8; 16],
+    pub port: u16,
+}
+
+impl NetAddr {
+    // IPv4 addresses are carried as IPv4-mapped IPv6 addresses (::ffff:a.b.c.d)
+    pub fn from_ipv4(services: u64, ip: [u8; 4], port: u16) -> Self {
+        let mut mapped = [0u8; 16];
+        mapped[10] = 0xff;
+        mapped[11] = 0xff;
+        mapped[12..].copy_from_slice(&ip);
+        NetAddr {
+            services,
+            ip: mapped,
+            port,
+        }
+    }
+
+    pub fn ipv4(&self) -> Option<[u8; 4]> {
+        if self.ip[..10].iter().all(|b| *b == 0) && self.ip[10..12] == [0xff, 0xff] {
+            let mut ip = [0u8; 4];
+            ip.copy_from_slice(&self.ip[12..]);
+            Some(ip)
+        } else {
+            None
+        }
+    }
+
+    pub fn parse(reader: &mut impl Read) -> io::Result<Self> {
+        let services = read_u64_le(reader)?;
+        let ip = read_array(reader)?;
+        // Unlike the rest of the protocol, the port is big endian
+        let port = u16::from_be_bytes(read_array(reader)?);
+        Ok(NetAddr { services, ip, port })
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(26);
+        result.extend_from_slice(&self.services.to_le_bytes());
+        result.extend_from_slice(&self.ip);
+        result.extend_from_slice(&self.port.to_be_bytes());
+        result
+    }
+}
+
+// A p2p message payload, identified in the envelope by its command
+pub trait Message: Sized {
+    const COMMAND: &'static [u8];
+
+    fn parse(reader: &mut impl Read) -> io::Result<Self>;
+    fn serialize(&self) -> Vec<u8>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PingMessage {
+    pub nonce: [u8; 8],
+}
+
+impl Message for PingMessage {
+    const COMMAND: &'static [u8] = b"ping";
+
+    fn parse(reader: &mut impl Read) -> io::Result<Self> {
+        Ok(PingMessage {
+            nonce: read_array(reader)?,
+        })
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        self.nonce.to_vec()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PongMessage {
+    pub nonce: [u8; 8],
+}
+
+impl Message for PongMessage {
+    const COMMAND: &'static [u8] = b"pong";
+
+    fn parse(reader: &mut impl Read) -> io::Result<Self> {
+        Ok(PongMessage {
+            nonce: read_array(reader)?,
+        })
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        self.nonce.to_vec()
+    }
+}
+
+// A peer expects its ping nonce echoed back in a pong
+pub fn handle_ping(ping: &PingMessage) -> PongMessage {
+    PongMessage { nonce: ping.nonce }
+}
+
+// Asks the peer not to relay transactions below this fee rate, in satoshis per kilobyte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeFilterMessage {
+    pub fee_rate: u64,
+}
+
+impl Message for FeeFilterMessage {
+    const COMMAND: &'static [u8] = b"feefilter";
+
+    fn parse(reader: &mut impl Read) -> io::Result<Self> {
+        Ok(FeeFilterMessage {
+            fee_rate: read_u64_le(reader)?,
+        })
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        self.fee_rate.to_le_bytes().to_vec()
+    }
+}
+
+// Asks the peer to announce new blocks with headers instead of inv
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SendHeadersMessage;
+
+impl Message for SendHeadersMessage {
+    const COMMAND: &'static [u8] = b"sendheaders";
+
+    fn parse(_reader: &mut impl Read) -> io::Result<Self> {
+        Ok(SendHeadersMessage)
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        Vec::new()
+    }
+}
+
+// Bitcoin Core's MAX_LOCATOR_SZ and MAX_INV_SZ; larger counts are rejected before allocating
+const MAX_LOCATOR_SIZE: u64 = 101;
+const MAX_INV_SIZE: u64 = 50_000;
+
+fn read_count(reader: &mut impl Read, max: u64) -> io::Result<u64> {
+    let count = varint::decode(reader)?;
+    if count > max {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("count {} is over the limit of {}", count, max),
+        ));
+    }
+    Ok(count)
+}
+
+// Asks for the inventory of blocks after the first locator hash the peer knows, up to hash_stop
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GetBlocksMessage {
+    pub version: u32,
+    // Known block hashes from the tip backwards, so the peer can find where the chains fork
+    pub locator: Vec<[u8; 32]>,
+    // All zeros asks for as many blocks as the peer will send
+    pub hash_stop: [u8; 32],
+}
+
+impl Message for GetBlocksMessage {
+    const COMMAND: &'static [u8] = b"getblocks";
+
+    fn parse(reader: &mut impl Read) -> io::Result<Self> {
+        let version = read_u32_le(reader)?;
+        let count = read_count(reader, MAX_LOCATOR_SIZE)?;
+        let mut locator = Vec::new();
+        for _ in 0..count {
+            locator.push(read_array(reader)?);
+        }
+        let hash_stop = read_array(reader)?;
+        Ok(GetBlocksMessage {
+            version,
+            locator,
+            hash_stop,
+        })
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = self.version.to_le_bytes().to_vec();
+        result.extend(varint::encode(self.locator.len() as u64));
+        for hash in &self.locator {
+            result.extend_from_slice(hash);
+        }
+        result.extend_from_slice(&self.hash_stop);
+        result
+    }
+}
+
+pub const MSG_TX: u32 = 1;
+pub const MSG_BLOCK: u32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Inventory {
+    pub kind: u32,
+    pub hash: [u8; 32],
+}
+
+// Announces transactions and blocks, and answers getblocks with the block hashes to download
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvMessage {
+    pub inventory: Vec<Inventory>,
+}
+
+impl InvMessage {
+    // The hashes to request with getdata when downloading full blocks
+    pub fn block_hashes(&self) -> Vec<[u8; 32]> {
+        self.inventory
+            .iter()
+            .filter(|item| item.kind == MSG_BLOCK)
+            .map(|item| item.hash)
+            .collect()
+    }
+}
+
+impl Message for InvMessage {
+    const COMMAND: &'static [u8] = b"inv";
+
+    fn parse(reader: &mut impl Read) -> io::Result<Self> {
+        let count = read_count(reader, MAX_INV_SIZE)?;
+        let mut inventory = Vec::new();
+        for _ in 0..count {
+            let kind = read_u32_le(reader)?;
+            let hash = read_array(reader)?;
+            inventory.push(Inventory { kind, hash });
+        }
+        Ok(InvMessage { inventory })
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = varint::encode(self.inventory.len() as u64);
+        for item in &self.inventory {
+            result.extend_from_slice(&item.kind.to_le_bytes());
+            result.extend_from_slice(&item.hash);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regtest_prefix_test() {
+        assert_eq!(Network::Regtest.p2pkh_prefix(), 0x6f);
+        assert_eq!(Network::Regtest.p2sh_prefix(), 0xc4);
+        assert_eq!(Network::Regtest.wif_prefix(), 0xef);
+        assert_eq!(Network::Regtest.bech32_hrp(), "bcrt");
+    }
+
+    #[test]
+    fn mainnet_prefix_test() {
+        assert_eq!(Network::Mainnet.p2pkh_prefix(), 0x00);
+        assert_eq!(Network::Mainnet.p2sh_prefix(), 0x05);
+        assert_eq!(Network::Mainnet.wif_prefix(), 0x80);
+        assert_eq!(Network::Mainnet.magic(), [0xf9, 0xbe, 0xb4, 0xd9]);
+    }
+
+    #[test]
+    fn from_testnet_flag_test() {
+        assert_eq!(Network::from(true), Network::Testnet);
+        assert_eq!(Network::from(false), Network::Mainnet);
+    }
+
+    #[test]
+    fn net_addr_round_trip_test() {
+        let addr = NetAddr::from_ipv4(1, [127, 0, 0, 1], 8333);
+        let bytes = addr.serialize();
+        let mut expected = vec![1, 0, 0, 0, 0, 0, 0, 0];
+        expected.extend_from_slice(&[0; 10]);
+        expected.extend_from_slice(&[0xff, 0xff, 127, 0, 0, 1, 0x20, 0x8d]);
+        assert_eq!(bytes, expected);
+
+        let parsed = NetAddr::parse(&mut &bytes[..]).unwrap();
+        assert_eq!(parsed, addr);
+        assert_eq!(parsed.ipv4(), Some([127, 0, 0, 1]));
+    }
+
+    #[test]
+    fn net_addr_ipv6_test() {
+        let mut ip = [0u8; 16];
+        ip[0] = 0x20;
+        ip[1] = 0x01;
+        ip[15] = 0x01;
+        let addr = NetAddr {
+            services: 0,
+            ip,
+            port: 18333,
+        };
+        assert_eq!(addr.ipv4(), None);
+        assert_eq!(NetAddr::parse(&mut &addr.serialize()[..]).unwrap(), addr);
+        assert!(NetAddr::parse(&mut &addr.serialize()[..25]).is_err());
+    }
+
+    #[test]
+    fn ping_pong_round_trip_test() {
+        let ping = PingMessage {
+            nonce: [1, 2, 3, 4, 5, 6, 7, 8],
+        };
+        assert_eq!(ping.serialize(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            PingMessage::parse(&mut &ping.serialize()[..]).unwrap(),
+            ping
+        );
+
+        let pong = PongMessage { nonce: [9; 8] };
+        assert_eq!(
+            PongMessage::parse(&mut &pong.serialize()[..]).unwrap(),
+            pong
+        );
+        assert!(PongMessage::parse(&mut &[0u8; 7][..]).is_err());
+        assert_eq!(PingMessage::COMMAND, b"ping");
+        assert_eq!(PongMessage::COMMAND, b"pong");
+    }
+
+    #[test]
+    fn handle_ping_test() {
+        let ping = PingMessage {
+            nonce: [0xde, 0xad, 0xbe, 0xef, 0x00, 0x11, 0x22, 0x33],
+        };
+        let pong = handle_ping(&ping);
+        assert_eq!(pong.nonce, ping.nonce);
+        assert_eq!(pong.serialize(), ping.serialize());
+    }
+
+    #[test]
+    fn fee_filter_round_trip_test() {
+        let message = FeeFilterMessage { fee_rate: 1000 };
+        let bytes = message.serialize();
+        assert_eq!(bytes, vec![0xe8, 0x03, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(FeeFilterMessage::parse(&mut &bytes[..]).unwrap(), message);
+        assert!(FeeFilterMessage::parse(&mut &bytes[..4]).is_err());
+    }
+
+    #[test]
+    fn send_headers_round_trip_test() {
+        let bytes = SendHeadersMessage.serialize();
+        assert!(bytes.is_empty());
+        assert_eq!(
+            SendHeadersMessage::parse(&mut &bytes[..]).unwrap(),
+            SendHeadersMessage
+        );
+    }
+
+    #[test]
+    fn get_blocks_round_trip_test() {
+        let message = GetBlocksMessage {
+            version: 70015,
+            locator: vec![[0xaa; 32], [0xbb; 32]],
+            hash_stop: [0; 32],
+        };
+        let bytes = message.serialize();
+        assert_eq!(bytes.len(), 4 + 1 + 64 + 32);
+        assert_eq!(bytes[..5], [0x7f, 0x11, 0x01, 0x00, 0x02]);
+        assert_eq!(GetBlocksMessage::parse(&mut &bytes[..]).unwrap(), message);
+        assert!(GetBlocksMessage::parse(&mut &bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn inv_round_trip_test() {
+        let message = InvMessage {
+            inventory: vec![
+                Inventory {
+                    kind: MSG_TX,
+                    hash: [1; 32],
+                },
+                Inventory {
+                    kind: MSG_BLOCK,
+                    hash: [2; 32],
+                },
+            ],
+        };
+        let bytes = message.serialize();
+        assert_eq!(bytes.len(), 1 + 2 * 36);
+        assert_eq!(InvMessage::parse(&mut &bytes[..]).unwrap(), message);
+    }
+
+    #[test]
+    fn inv_block_hashes_from_stream_test() {
+        let mut stream = vec![0x02];
+        for hash in &[[0x11; 32], [0x22; 32]] {
+            stream.extend_from_slice(&MSG_BLOCK.to_le_bytes());
+            stream.extend_from_slice(hash);
+        }
+        let mut reader = std::io::Cursor::new(stream);
+        let inv = InvMessage::parse(&mut reader).unwrap();
+        assert_eq!(inv.block_hashes(), vec![[0x11; 32], [0x22; 32]]);
+        assert_eq!(reader.position(), 73);
+    }
+
+    #[test]
+    fn oversized_count_test() {
+        // 0xff followed by u64::MAX, then more data than any real message would carry
+        let mut bytes = vec![0xff; 9];
+        bytes.extend_from_slice(&[0; 1024]);
+        let error = InvMessage::parse(&mut &bytes[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let mut bytes = 70015u32.to_le_bytes().to_vec();
+        bytes.extend(varint::encode(MAX_LOCATOR_SIZE + 1));
+        bytes.extend_from_slice(&[0; 32 * 103]);
+        let error = GetBlocksMessage::parse(&mut &bytes[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // The limits themselves are still accepted
+        let mut bytes = varint::encode(MAX_INV_SIZE);
+        bytes.extend_from_slice(&[0; 36 * MAX_INV_SIZE as usize]);
+        assert_eq!(
+            InvMessage::parse(&mut &bytes[..]).unwrap().inventory.len(),
+            MAX_INV_SIZE as usize
+        );
+    }
+}
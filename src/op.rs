@@ -0,0 +1,852 @@
+use crate::hashing::{hash160, hash256, ripemd160, sha1, sha256};
+
+pub type Stack = Vec<Vec<u8>>;
+pub type OpFunction = fn(&mut Stack) -> bool;
+
+const OP_IF: u8 = 0x63;
+const OP_NOTIF: u8 = 0x64;
+const OP_ELSE: u8 = 0x67;
+const OP_ENDIF: u8 = 0x68;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Op(u8),
+    Element(Vec<u8>),
+}
+
+pub fn encode_num(num: i64) -> Vec<u8> {
+    if num == 0 {
+        return Vec::new();
+    }
+
+    let negative = num < 0;
+    let mut abs_num = num.unsigned_abs();
+    let mut result = Vec::new();
+    while abs_num > 0 {
+        result.push((abs_num & 0xff) as u8);
+        abs_num >>= 8;
+    }
+
+    // The top bit of the last byte is the sign bit, so add an extra byte if it is already taken
+    let last = result.len() - 1;
+    if result[last] & 0x80 != 0 {
+        result.push(if negative { 0x80 } else { 0x00 });
+    } else if negative {
+        result[last] |= 0x80;
+    }
+    result
+}
+
+pub fn decode_num(element: &[u8]) -> i64 {
+    if element.is_empty() {
+        return 0;
+    }
+    if element.len() > 8 {
+        panic!("Cannot decode a {}-byte element into an i64", element.len());
+    }
+
+    // Little endian, so the most significant byte comes last
+    let mut big_endian = element.iter().rev();
+    let first = *big_endian.next().unwrap();
+    let negative = first & 0x80 != 0;
+    let mut result = (first & 0x7f) as i64;
+    for c in big_endian {
+        result = (result << 8) + *c as i64;
+    }
+
+    if negative {
+        -result
+    } else {
+        result
+    }
+}
+
+// Like decode_num, but None for elements longer than max_len bytes or not minimally encoded
+pub fn decode_num_checked(element: &[u8], max_len: usize) -> Option<i64> {
+    if element.len() > max_len || element.len() > 8 {
+        return None;
+    }
+    // The last byte may only be 0x00 or 0x80 when it is needed to hold the sign bit
+    if let Some((last, rest)) = element.split_last() {
+        if last & 0x7f == 0 && rest.last().is_none_or(|c| c & 0x80 == 0) {
+            return None;
+        }
+    }
+    Some(decode_num(element))
+}
+
+// An element is false when it encodes zero, including negative zero
+pub fn cast_to_bool(element: &[u8]) -> bool {
+    match element.split_last() {
+        None => false,
+        Some((last, rest)) => rest.iter().any(|c| *c != 0) || (*last & 0x7f) != 0,
+    }
+}
+
+// Numeric opcodes only take 4-byte operands, so their results always fit in an i64
+const MAX_NUM_SIZE: usize = 4;
+
+// None when the stack is empty or the top element is not a valid numeric operand
+fn pop_num(stack: &mut Stack) -> Option<i64> {
+    stack
+        .pop()
+        .and_then(|element| decode_num_checked(&element, MAX_NUM_SIZE))
+}
+
+fn push_bool(stack: &mut Stack, value: bool) {
+    stack.push(encode_num(if value { 1 } else { 0 }));
+}
+
+// Applies a function to the top element, replacing it with the result
+fn unary_op(stack: &mut Stack, f: impl Fn(i64) -> i64) -> bool {
+    match pop_num(stack) {
+        Some(element) => {
+            stack.push(encode_num(f(element)));
+            true
+        }
+        None => false,
+    }
+}
+
+// Pops the top two elements and pushes f(second, top)
+fn binary_op(stack: &mut Stack, f: impl Fn(i64, i64) -> i64) -> bool {
+    match (pop_num(stack), pop_num(stack)) {
+        (Some(element1), Some(element2)) => {
+            stack.push(encode_num(f(element2, element1)));
+            true
+        }
+        _ => false,
+    }
+}
+
+pub fn op_verify(stack: &mut Stack) -> bool {
+    match stack.pop() {
+        Some(element) => cast_to_bool(&element),
+        None => false,
+    }
+}
+
+pub fn op_return(_stack: &mut Stack) -> bool {
+    false
+}
+
+pub fn op_1add(stack: &mut Stack) -> bool {
+    unary_op(stack, |a| a + 1)
+}
+
+pub fn op_1sub(stack: &mut Stack) -> bool {
+    unary_op(stack, |a| a - 1)
+}
+
+pub fn op_negate(stack: &mut Stack) -> bool {
+    unary_op(stack, |a| -a)
+}
+
+pub fn op_abs(stack: &mut Stack) -> bool {
+    unary_op(stack, |a| a.abs())
+}
+
+pub fn op_not(stack: &mut Stack) -> bool {
+    unary_op(stack, |a| (a == 0) as i64)
+}
+
+pub fn op_0notequal(stack: &mut Stack) -> bool {
+    unary_op(stack, |a| (a != 0) as i64)
+}
+
+pub fn op_add(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| a + b)
+}
+
+pub fn op_sub(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| a - b)
+}
+
+pub fn op_booland(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| (a != 0 && b != 0) as i64)
+}
+
+pub fn op_boolor(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| (a != 0 || b != 0) as i64)
+}
+
+pub fn op_numequal(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| (a == b) as i64)
+}
+
+pub fn op_numequalverify(stack: &mut Stack) -> bool {
+    op_numequal(stack) && op_verify(stack)
+}
+
+pub fn op_numnotequal(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| (a != b) as i64)
+}
+
+pub fn op_lessthan(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| (a < b) as i64)
+}
+
+pub fn op_greaterthan(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| (a > b) as i64)
+}
+
+pub fn op_lessthanorequal(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| (a <= b) as i64)
+}
+
+pub fn op_greaterthanorequal(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| (a >= b) as i64)
+}
+
+pub fn op_min(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| a.min(b))
+}
+
+pub fn op_max(stack: &mut Stack) -> bool {
+    binary_op(stack, |a, b| a.max(b))
+}
+
+pub fn op_within(stack: &mut Stack) -> bool {
+    match (pop_num(stack), pop_num(stack), pop_num(stack)) {
+        (Some(maximum), Some(minimum), Some(element)) => {
+            push_bool(stack, minimum <= element && element < maximum);
+            true
+        }
+        _ => false,
+    }
+}
+
+// Replaces the top element with its digest
+fn hash_op<const N: usize>(stack: &mut Stack, f: fn(&[u8]) -> [u8; N]) -> bool {
+    match stack.pop() {
+        Some(element) => {
+            stack.push(f(&element).to_vec());
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn op_ripemd160(stack: &mut Stack) -> bool {
+    hash_op(stack, ripemd160)
+}
+
+pub fn op_sha1(stack: &mut Stack) -> bool {
+    hash_op(stack, sha1)
+}
+
+pub fn op_sha256(stack: &mut Stack) -> bool {
+    hash_op(stack, sha256)
+}
+
+pub fn op_hash160(stack: &mut Stack) -> bool {
+    hash_op(stack, hash160)
+}
+
+pub fn op_hash256(stack: &mut Stack) -> bool {
+    hash_op(stack, hash256)
+}
+
+// Splits the commands up to the matching OP_ENDIF into the two branches of a conditional,
+// returning None when the conditional is never closed
+fn split_branches(items: &mut Vec<Command>) -> Option<(Vec<Command>, Vec<Command>)> {
+    let mut true_items = Vec::new();
+    let mut false_items = Vec::new();
+    let mut in_false_branch = false;
+    let mut num_endifs_needed = 1;
+    while !items.is_empty() {
+        let item = items.remove(0);
+        let current = if in_false_branch {
+            &mut false_items
+        } else {
+            &mut true_items
+        };
+        match item {
+            Command::Op(OP_IF) | Command::Op(OP_NOTIF) => {
+                num_endifs_needed += 1;
+                current.push(item);
+            }
+            Command::Op(OP_ELSE) if num_endifs_needed == 1 => in_false_branch = true,
+            Command::Op(OP_ENDIF) => {
+                if num_endifs_needed == 1 {
+                    return Some((true_items, false_items));
+                }
+                num_endifs_needed -= 1;
+                current.push(item);
+            }
+            _ => current.push(item),
+        }
+    }
+    None
+}
+
+// Puts the branch selected by the popped condition in front of the remaining commands
+fn conditional(stack: &mut Stack, items: &mut Vec<Command>, negate: bool) -> bool {
+    if stack.is_empty() {
+        return false;
+    }
+    let (true_items, false_items) = match split_branches(items) {
+        Some(branches) => branches,
+        None => return false,
+    };
+    let condition = cast_to_bool(&stack.pop().unwrap());
+    let branch = if condition != negate {
+        true_items
+    } else {
+        false_items
+    };
+    items.splice(0..0, branch);
+    true
+}
+
+pub fn op_if(stack: &mut Stack, items: &mut Vec<Command>) -> bool {
+    conditional(stack, items, false)
+}
+
+pub fn op_notif(stack: &mut Stack, items: &mut Vec<Command>) -> bool {
+    conditional(stack, items, true)
+}
+
+pub fn op_code_function(op_code: u8) -> Option<OpFunction> {
+    let f: OpFunction = match op_code {
+        0x69 => op_verify,
+        0x6a => op_return,
+        0x8b => op_1add,
+        0x8c => op_1sub,
+        0x8f => op_negate,
+        0x90 => op_abs,
+        0x91 => op_not,
+        0x92 => op_0notequal,
+        0x93 => op_add,
+        0x94 => op_sub,
+        0x9a => op_booland,
+        0x9b => op_boolor,
+        0x9c => op_numequal,
+        0x9d => op_numequalverify,
+        0x9e => op_numnotequal,
+        0x9f => op_lessthan,
+        0xa0 => op_greaterthan,
+        0xa1 => op_lessthanorequal,
+        0xa2 => op_greaterthanorequal,
+        0xa3 => op_min,
+        0xa4 => op_max,
+        0xa5 => op_within,
+        0xa6 => op_ripemd160,
+        0xa7 => op_sha1,
+        0xa8 => op_sha256,
+        0xa9 => op_hash160,
+        0xaa => op_hash256,
+        _ => return None,
+    };
+    Some(f)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScriptError {
+    UnknownOp(u8),
+    OpFailed(u8),
+}
+
+fn execute(stack: &mut Stack, mut cmds: Vec<Command>) -> Result<(), ScriptError> {
+    while !cmds.is_empty() {
+        let (op_code, ok) = match cmds.remove(0) {
+            Command::Element(element) => {
+                stack.push(element);
+                continue;
+            }
+            Command::Op(OP_IF) => (OP_IF, op_if(stack, &mut cmds)),
+            Command::Op(OP_NOTIF) => (OP_NOTIF, op_notif(stack, &mut cmds)),
+            Command::Op(op_code) => match op_code_function(op_code) {
+                Some(f) => (op_code, f(stack)),
+                None => return Err(ScriptError::UnknownOp(op_code)),
+            },
+        };
+        if !ok {
+            return Err(ScriptError::OpFailed(op_code));
+        }
+    }
+    Ok(())
+}
+
+fn evaluate_result(cmds: &[Command]) -> Result<bool, ScriptError> {
+    let mut stack = Stack::new();
+    execute(&mut stack, cmds.to_vec())?;
+    Ok(stack.pop().is_some_and(|element| cast_to_bool(&element)))
+}
+
+// Runs the commands and reports whether they left a true value on top of the stack
+pub fn evaluate(cmds: &[Command]) -> bool {
+    evaluate_result(cmds).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::decode_hex;
+
+    #[test]
+    fn encode_num_test() {
+        assert_eq!(encode_num(0), vec![]);
+        assert_eq!(encode_num(127), vec![0x7f]);
+        assert_eq!(encode_num(-1), vec![0x81]);
+        assert_eq!(encode_num(128), vec![0x80, 0x00]);
+        assert_eq!(encode_num(-129), vec![0x81, 0x80]);
+    }
+
+    #[test]
+    fn decode_num_test() {
+        assert_eq!(decode_num(&[]), 0);
+        assert_eq!(decode_num(&[0x7f]), 127);
+        assert_eq!(decode_num(&[0x81]), -1);
+        assert_eq!(decode_num(&[0x80, 0x00]), 128);
+        assert_eq!(decode_num(&[0x81, 0x80]), -129);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot decode a 9-byte element into an i64")]
+    fn decode_num_too_long_test() {
+        decode_num(&[1, 0, 0, 0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn decode_num_checked_test() {
+        assert_eq!(decode_num_checked(&[], 4), Some(0));
+        assert_eq!(decode_num_checked(&[0x80, 0x00], 4), Some(128));
+        assert_eq!(decode_num_checked(&[0x81, 0x80], 4), Some(-129));
+        assert_eq!(
+            decode_num_checked(&[0xff, 0xff, 0xff, 0x7f], 4),
+            Some(0x7fff_ffff)
+        );
+        // Non-minimal encodings of 1, -1 and zero
+        assert_eq!(decode_num_checked(&[0x01, 0x00], 4), None);
+        assert_eq!(decode_num_checked(&[0x01, 0x80], 4), None);
+        assert_eq!(decode_num_checked(&[0x00], 4), None);
+        assert_eq!(decode_num_checked(&[0x80], 4), None);
+        // Longer than the cap
+        assert_eq!(decode_num_checked(&[0x01, 0x02, 0x03, 0x04, 0x05], 4), None);
+        assert_eq!(decode_num_checked(&[1, 0, 0, 0, 0, 0, 0, 0, 1], 9), None);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        for n in [
+            0,
+            1,
+            -1,
+            127,
+            -127,
+            128,
+            -128,
+            255,
+            -255,
+            256,
+            32767,
+            -32768,
+            1 << 40,
+        ] {
+            assert_eq!(decode_num(&encode_num(n)), n);
+        }
+    }
+
+    fn stack(nums: &[i64]) -> Stack {
+        nums.iter().map(|n| encode_num(*n)).collect()
+    }
+
+    #[test]
+    fn op_1add_test() {
+        let mut s = stack(&[5]);
+        assert!(op_1add(&mut s));
+        assert_eq!(s, stack(&[6]));
+    }
+
+    #[test]
+    fn op_1sub_test() {
+        let mut s = stack(&[0]);
+        assert!(op_1sub(&mut s));
+        assert_eq!(s, stack(&[-1]));
+    }
+
+    #[test]
+    fn op_negate_test() {
+        let mut s = stack(&[3]);
+        assert!(op_negate(&mut s));
+        assert_eq!(s, stack(&[-3]));
+    }
+
+    #[test]
+    fn op_abs_test() {
+        let mut s = stack(&[-129]);
+        assert!(op_abs(&mut s));
+        assert_eq!(s, stack(&[129]));
+    }
+
+    #[test]
+    fn op_not_test() {
+        let mut s = stack(&[0, 7]);
+        assert!(op_not(&mut s));
+        assert_eq!(s, stack(&[0, 0]));
+        assert!(op_not(&mut s));
+        assert_eq!(s, stack(&[0, 1]));
+    }
+
+    #[test]
+    fn op_0notequal_test() {
+        let mut s = stack(&[0, 7]);
+        assert!(op_0notequal(&mut s));
+        assert_eq!(s, stack(&[0, 1]));
+    }
+
+    #[test]
+    fn op_add_test() {
+        let mut s = stack(&[2, 3]);
+        assert!(op_add(&mut s));
+        assert_eq!(s, stack(&[5]));
+    }
+
+    #[test]
+    fn op_sub_test() {
+        let mut s = stack(&[2, 3]);
+        assert!(op_sub(&mut s));
+        assert_eq!(s, stack(&[-1]));
+    }
+
+    #[test]
+    fn op_booland_test() {
+        let mut s = stack(&[1, 0]);
+        assert!(op_booland(&mut s));
+        assert_eq!(s, stack(&[0]));
+        let mut s = stack(&[1, -1]);
+        assert!(op_booland(&mut s));
+        assert_eq!(s, stack(&[1]));
+    }
+
+    #[test]
+    fn op_boolor_test() {
+        let mut s = stack(&[0, 0]);
+        assert!(op_boolor(&mut s));
+        assert_eq!(s, stack(&[0]));
+        let mut s = stack(&[0, 2]);
+        assert!(op_boolor(&mut s));
+        assert_eq!(s, stack(&[1]));
+    }
+
+    #[test]
+    fn op_numequal_test() {
+        let mut s = stack(&[4, 4]);
+        assert!(op_numequal(&mut s));
+        assert_eq!(s, stack(&[1]));
+    }
+
+    #[test]
+    fn op_numequalverify_test() {
+        let mut s = stack(&[9, 4, 4]);
+        assert!(op_numequalverify(&mut s));
+        assert_eq!(s, stack(&[9]));
+        let mut s = stack(&[4, 5]);
+        assert!(!op_numequalverify(&mut s));
+    }
+
+    #[test]
+    fn op_lessthan_test() {
+        let mut s = stack(&[2, 3]);
+        assert!(op_lessthan(&mut s));
+        assert_eq!(s, stack(&[1]));
+    }
+
+    #[test]
+    fn op_greaterthan_test() {
+        let mut s = stack(&[2, 3]);
+        assert!(op_greaterthan(&mut s));
+        assert_eq!(s, stack(&[0]));
+    }
+
+    #[test]
+    fn op_min_test() {
+        let mut s = stack(&[-2, 3]);
+        assert!(op_min(&mut s));
+        assert_eq!(s, stack(&[-2]));
+    }
+
+    #[test]
+    fn op_max_test() {
+        let mut s = stack(&[-2, 3]);
+        assert!(op_max(&mut s));
+        assert_eq!(s, stack(&[3]));
+    }
+
+    #[test]
+    fn op_within_test() {
+        let mut s = stack(&[3, 3, 5]);
+        assert!(op_within(&mut s));
+        assert_eq!(s, stack(&[1]));
+        let mut s = stack(&[5, 3, 5]);
+        assert!(op_within(&mut s));
+        assert_eq!(s, stack(&[0]));
+    }
+
+    #[test]
+    fn stack_underflow_test() {
+        let mut s = stack(&[1]);
+        assert!(!op_add(&mut s));
+        assert!(!op_within(&mut stack(&[1, 2])));
+        assert!(!op_1add(&mut vec![]));
+    }
+
+    #[test]
+    fn oversized_operand_test() {
+        let mut s = vec![vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]];
+        assert!(!op_1add(&mut s));
+        assert!(!op_add(&mut vec![vec![0xff; 8], vec![0xff; 8]]));
+        assert!(!op_negate(&mut vec![vec![0x01; 9]]));
+        assert!(!op_within(&mut vec![
+            vec![0x01; 5],
+            encode_num(0),
+            encode_num(9)
+        ]));
+        // Non-minimal encodings are rejected too
+        assert!(!op_add(&mut vec![vec![0x01, 0x00], encode_num(1)]));
+    }
+
+    #[test]
+    fn overflow_test() {
+        let max = 0x7fff_ffff;
+        let mut s = stack(&[max, max]);
+        assert!(op_add(&mut s));
+        assert_eq!(s, stack(&[2 * max]));
+        // The 5-byte sum cannot be fed back into another numeric opcode
+        assert_eq!(s[0].len(), 5);
+        assert!(!op_1add(&mut s));
+
+        let mut s = stack(&[-max, max]);
+        assert!(op_sub(&mut s));
+        assert_eq!(s, stack(&[-2 * max]));
+        let mut s = stack(&[-max]);
+        assert!(op_negate(&mut s));
+        assert_eq!(s, stack(&[max]));
+    }
+
+    #[test]
+    fn op_code_function_test() {
+        let mut s = stack(&[2, 3]);
+        let f = op_code_function(0x93).unwrap();
+        assert!(f(&mut s));
+        assert_eq!(s, stack(&[5]));
+        assert!(op_code_function(0x00).is_none());
+    }
+
+    #[test]
+    fn op_ripemd160_test() {
+        let mut s = vec![b"abc".to_vec()];
+        assert!(op_ripemd160(&mut s));
+        assert_eq!(
+            s,
+            vec![decode_hex("8eb208f7e05d987a9b044a8e98c6b087f15a0bfc")]
+        );
+    }
+
+    #[test]
+    fn op_sha1_test() {
+        let mut s = vec![b"abc".to_vec()];
+        assert!(op_sha1(&mut s));
+        assert_eq!(
+            s,
+            vec![decode_hex("a9993e364706816aba3e25717850c26c9cd0d89d")]
+        );
+    }
+
+    #[test]
+    fn op_sha256_test() {
+        let mut s = vec![b"abc".to_vec()];
+        assert!(op_sha256(&mut s));
+        assert_eq!(
+            s,
+            vec![decode_hex(
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+            )]
+        );
+    }
+
+    #[test]
+    fn op_hash160_test() {
+        let mut s = vec![b"abc".to_vec()];
+        assert!(op_hash160(&mut s));
+        assert_eq!(
+            s,
+            vec![decode_hex("bb1be98c142444d7a56aa3981c3942a978e4dc33")]
+        );
+    }
+
+    #[test]
+    fn op_hash256_test() {
+        let mut s = vec![b"abc".to_vec()];
+        assert!(op_hash256(&mut s));
+        assert_eq!(
+            s,
+            vec![decode_hex(
+                "4f8b42c22dd3729b519ba6f68d2da7cc5b2d606d05daed5ad5128cc03e6c6358"
+            )]
+        );
+        assert!(!op_hash256(&mut vec![]));
+    }
+
+    fn run(stack: &mut Stack, cmds: Vec<Command>) -> bool {
+        execute(stack, cmds).is_ok()
+    }
+
+    fn num(n: i64) -> Command {
+        Command::Element(encode_num(n))
+    }
+
+    #[test]
+    fn op_if_test() {
+        // <cond> OP_IF 2 OP_ELSE 3 OP_ENDIF 1 OP_ADD
+        let script = |cond| {
+            vec![
+                num(cond),
+                Command::Op(OP_IF),
+                num(2),
+                Command::Op(OP_ELSE),
+                num(3),
+                Command::Op(OP_ENDIF),
+                num(1),
+                Command::Op(0x93),
+            ]
+        };
+        let mut s = vec![];
+        assert!(run(&mut s, script(1)));
+        assert_eq!(s, stack(&[3]));
+        let mut s = vec![];
+        assert!(run(&mut s, script(0)));
+        assert_eq!(s, stack(&[4]));
+    }
+
+    #[test]
+    fn op_notif_test() {
+        let script = |cond| {
+            vec![
+                num(cond),
+                Command::Op(OP_NOTIF),
+                num(2),
+                Command::Op(OP_ELSE),
+                num(3),
+                Command::Op(OP_ENDIF),
+            ]
+        };
+        let mut s = vec![];
+        assert!(run(&mut s, script(1)));
+        assert_eq!(s, stack(&[3]));
+        let mut s = vec![];
+        assert!(run(&mut s, script(0)));
+        assert_eq!(s, stack(&[2]));
+    }
+
+    #[test]
+    fn nested_if_test() {
+        // <a> <b> OP_IF OP_IF 1 OP_ELSE 2 OP_ENDIF OP_ELSE OP_IF 3 OP_ELSE 4 OP_ENDIF OP_ENDIF
+        let script = |a, b| {
+            vec![
+                num(a),
+                num(b),
+                Command::Op(OP_IF),
+                Command::Op(OP_IF),
+                num(1),
+                Command::Op(OP_ELSE),
+                num(2),
+                Command::Op(OP_ENDIF),
+                Command::Op(OP_ELSE),
+                Command::Op(OP_IF),
+                num(3),
+                Command::Op(OP_ELSE),
+                num(4),
+                Command::Op(OP_ENDIF),
+                Command::Op(OP_ENDIF),
+            ]
+        };
+        for (a, b, expected) in [(1, 1, 1), (0, 1, 2), (1, 0, 3), (0, 0, 4)] {
+            let mut s = vec![];
+            assert!(run(&mut s, script(a, b)));
+            assert_eq!(s, stack(&[expected]));
+        }
+    }
+
+    #[test]
+    fn unbalanced_if_test() {
+        let mut s = stack(&[1]);
+        let mut items = vec![num(2), Command::Op(OP_ELSE), num(3)];
+        assert!(!op_if(&mut s, &mut items));
+        assert!(!op_if(&mut vec![], &mut vec![Command::Op(OP_ENDIF)]));
+    }
+
+    #[test]
+    fn cast_to_bool_test() {
+        assert!(!cast_to_bool(&[]));
+        assert!(!cast_to_bool(&[0x00, 0x00]));
+        assert!(!cast_to_bool(&[0x00, 0x80]));
+        assert!(cast_to_bool(&[0x01, 0x80]));
+        assert!(cast_to_bool(&[0x81]));
+    }
+
+    #[test]
+    fn op_verify_test() {
+        let mut s = stack(&[5, 1]);
+        assert!(op_verify(&mut s));
+        assert_eq!(s, stack(&[5]));
+        assert!(!op_verify(&mut stack(&[0])));
+        assert!(!op_verify(&mut vec![]));
+    }
+
+    #[test]
+    fn evaluate_empty_stack_test() {
+        assert!(!evaluate(&[]));
+        assert_eq!(evaluate_result(&[num(1), Command::Op(0x69)]), Ok(false));
+    }
+
+    #[test]
+    fn evaluate_failed_verify_test() {
+        let cmds = [num(2), num(3), Command::Op(0x9d), num(1)];
+        assert!(!evaluate(&cmds));
+        assert_eq!(evaluate_result(&cmds), Err(ScriptError::OpFailed(0x9d)));
+        assert_eq!(
+            evaluate_result(&[num(1), Command::Op(0x6a)]),
+            Err(ScriptError::OpFailed(0x6a))
+        );
+        assert_eq!(
+            evaluate_result(&[Command::Op(0xff)]),
+            Err(ScriptError::UnknownOp(0xff))
+        );
+    }
+
+    #[test]
+    fn evaluate_oversized_operand_test() {
+        let cmds = [
+            Command::Element(vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]),
+            Command::Op(0x8b),
+        ];
+        assert!(!evaluate(&cmds));
+        assert_eq!(evaluate_result(&cmds), Err(ScriptError::OpFailed(0x8b)));
+        let cmds = [
+            Command::Element(vec![0xff; 8]),
+            Command::Element(vec![0xff; 8]),
+            Command::Op(0x93),
+        ];
+        assert!(!evaluate(&cmds));
+        assert!(!evaluate(&[
+            Command::Element(vec![0x01; 9]),
+            Command::Op(0x8f)
+        ]));
+    }
+
+    #[test]
+    fn evaluate_success_test() {
+        // 2 3 OP_ADD 5 OP_NUMEQUALVERIFY 1
+        let cmds = [
+            num(2),
+            num(3),
+            Command::Op(0x93),
+            num(5),
+            Command::Op(0x9d),
+            num(1),
+        ];
+        assert!(evaluate(&cmds));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::field_element::FieldElement;
 use crate::forward_ref_binop;
 use std::fmt;
-use std::ops::{Add, Mul};
+use std::ops::{Add, Mul, Neg};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Coordinate {
@@ -31,6 +31,7 @@ impl fmt::Display for Coordinate {
     }
 }
 
+// Derived equality compares curves too, so Inf on one curve is not equal to Inf on another
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Point {
     pub a: FieldElement,
@@ -42,7 +43,7 @@ pub struct Point {
 impl Point {
     fn new(x: Coordinate, y: Coordinate, a: FieldElement, b: FieldElement) -> Self {
         let result = Point { a, b, x, y };
-if !result.is_on_curve() {
+        if !result.is_on_curve() {
             panic!("({}, {}) is not on the curve.", result.x, result.y);
         }
 
@@ -52,66 +53,170 @@ if !result.is_on_curve() {
     fn is_on_curve(&self) -> bool {
         match (&self.x, &self.y) {
             (Coordinate::Inf, Coordinate::Inf) => true,
-            (Coordinate::Num(x), Coordinate::Num(y)) => y.pow(2) == x.pow(3) + self.a * x + self.b,
+            (Coordinate::Num(x), Coordinate::Num(y)) => {
+                y.square() == x.pow(3) + self.a * x + self.b
+            }
             (_, _) => false,
         }
     }
+
+    // True when both points lie on the same curve over the same field, whatever their coordinates
+    pub fn is_same_group(&self, other: &Point) -> bool {
+        self.a == other.a && self.b == other.b
+    }
+
+    // True when other is -self, i.e. the reflection of self over the x-axis
+    pub fn is_inverse_of(&self, other: &Point) -> bool {
+        if !self.is_same_group(other) {
+            return false;
+        }
+        match (self.x, self.y, other.x, other.y) {
+            (Coordinate::Inf, Coordinate::Inf, Coordinate::Inf, Coordinate::Inf) => true,
+            (
+                Coordinate::Num(x1),
+                Coordinate::Num(y1),
+                Coordinate::Num(x2),
+                Coordinate::Num(y2),
+            ) => x1 == x2 && (y1 + y2).num == 0,
+            _ => false,
+        }
+    }
+
+    // Multiplies by a scalar from the scalar field; k.prime is expected to be the order of this point
+    pub fn mul_fe(&self, k: &FieldElement) -> Point {
+        k.num * *self
+    }
+
+    // Yields P, 2P, 3P, ... up to and including Inf, where the group closes
+    pub fn subgroup(&self) -> impl Iterator<Item = Point> {
+        let generator = *self;
+        std::iter::successors(Some(generator), move |current| match current.x {
+            Coordinate::Inf => None,
+            _ => Some(current + generator),
+        })
+    }
+
+    // [P, 2P, 4P, ...], one doubling per bit, for reuse across many mul_with_table calls
+    pub fn precompute(&self, bits: usize) -> Vec<Point> {
+        std::iter::successors(Some(*self), |current| Some(current + current))
+            .take(bits)
+            .collect()
+    }
+
+    // k * self, adding up table entries instead of doubling; table must come from self.precompute
+    pub fn mul_with_table(&self, k: u64, table: &[Point]) -> Point {
+        let bits = (64 - k.leading_zeros()) as usize;
+        if bits > table.len() {
+            panic!(
+                "Scalar needs {} bits but the table only covers {}",
+                bits,
+                table.len()
+            );
+        }
+
+        (0..bits).filter(|i| k >> i & 1 == 1).fold(
+            Point::new(Coordinate::Inf, Coordinate::Inf, self.a, self.b),
+            |result, i| result + table[i],
+        )
+    }
 }
 
 impl Add for Point {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
+        if self.a.prime != other.a.prime {
+            panic!(
+                "Cannot add points over different fields: F_{} and F_{}",
+                self.a.prime, other.a.prime
+            );
+        }
+
         match (&self.x, &other.x) {
             // Inf is the unit source in addition
             (Coordinate::Inf, _) => other,
             (_, Coordinate::Inf) => self,
             (_, _) => {
+                // P + (-P) is a vertical line, which meets the curve again only at Inf
+                if self.is_inverse_of(&other) {
+                    return Point::new(Coordinate::Inf, Coordinate::Inf, self.a, self.b);
+                }
+
                 let x1 = &self.x.num();
                 let y1 = &self.y.num();
                 let x2 = &other.x.num();
                 let y2 = &other.y.num();
-                let p = x1.prime;
 
                 // Intersection of a line passing through x1 and x2 with an elliptic curve
-                if x1 != x2 {
-                    let s = (y2 - y1) / (x2 - x1);
-                    let x3 = &s.pow(2) - x1 - x2;
-                    let y3 = &s * (x1 - &x3) - y1;
-                    return Point::new(Coordinate::Num(x3), Coordinate::Num(y3), self.a, self.b);
-                }
-
-                // When it is a tangent line
-                if y1 == y2 && y1 != &FieldElement::new(0, p) {
-                    let s = (FieldElement::new(3, p) * x1.pow(2) + &self.a)
-                        / (FieldElement::new(2, p) * y1);
-                    let x3 = &s.pow(2) - FieldElement::new(2, p) * x1;
-                    let y3 = &s * (x1 - &x3) - y1;
+                if let Some(dx) = x2.sub_nonzero(x1) {
+                    let s = (y2 - y1) / dx;
+                    let x3 = s.square() - x1 - x2;
+                    let y3 = s * (x1 - x3) - y1;
                     return Point::new(Coordinate::Num(x3), Coordinate::Num(y3), self.a, self.b);
                 }
 
-                // When the slope is zero (vertical)
-                Point::new(Coordinate::Inf, Coordinate::Inf, self.a, self.b)
+                // Otherwise the points are equal, so use the tangent line
+                let s = (3 * x1.square() + self.a) / (2 * y1);
+                let x3 = s.square() - 2 * x1;
+                let y3 = s * (x1 - x3) - y1;
+                Point::new(Coordinate::Num(x3), Coordinate::Num(y3), self.a, self.b)
             }
         }
     }
 }
 forward_ref_binop! { impl Add, add for Point }
 
+// The reflection over the x-axis; Inf is its own negation
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        match self.y {
+            Coordinate::Inf => self,
+            Coordinate::Num(y) => Point {
+                y: Coordinate::Num(-y),
+                ..self
+            },
+        }
+    }
+}
+
+// With the debug-checks feature, the base point of a scalar multiplication is checked up front.
+// Every intermediate sum and doubling already goes through the check in Point::new.
+#[cfg(feature = "debug-checks")]
+fn check_on_curve(point: &Point) {
+    if !point.is_on_curve() {
+        panic!(
+            "Base point ({}, {}) of scalar multiplication is not on the curve",
+            point.x, point.y
+        );
+    }
+}
+
+#[cfg(not(feature = "debug-checks"))]
+fn check_on_curve(_point: &Point) {}
+
 impl Mul<Point> for i64 {
     type Output = Point;
 
     fn mul(self, other: Point) -> Point {
-        let mut coef = self;
-        let mut current = other;
+        check_on_curve(&other);
+        match self {
+            0 => return Point::new(Coordinate::Inf, Coordinate::Inf, other.a, other.b),
+            1 => return other,
+            _ => {}
+        }
+
+        // -k * P is k * (-P); unsigned_abs keeps i64::MIN representable
+        let mut coef = self.unsigned_abs();
+        let mut current = if self < 0 { -other } else { other };
         let mut result = Point::new(Coordinate::Inf, Coordinate::Inf, other.a, other.b);
         while coef != 0 {
             if coef & 1 == 1 {
-                result = result + &current;
+                result = result + current;
             }
-            current = &current + current;
+            current = current + current;
             coef >>= 1;
-            println!("coef: {}", coef);
         }
         result
     }
@@ -142,6 +247,63 @@ mod tests {
         assert_eq!(p, p);
     }
 
+    #[test]
+    fn infinity_equality_test() {
+        let prime = 223;
+        let inf1 = Point::new(
+            Coordinate::Inf,
+            Coordinate::Inf,
+            FieldElement::new(0, prime),
+            FieldElement::new(7, prime),
+        );
+        let inf2 = Point::new(
+            Coordinate::Inf,
+            Coordinate::Inf,
+            FieldElement::new(0, prime),
+            FieldElement::new(7, prime),
+        );
+        assert_eq!(inf1, inf2);
+        assert!(inf1.is_same_group(&inf2));
+
+        let other_field = Point::new(
+            Coordinate::Inf,
+            Coordinate::Inf,
+            FieldElement::new(0, 103),
+            FieldElement::new(7, 103),
+        );
+        assert_ne!(inf1, other_field);
+        assert!(!inf1.is_same_group(&other_field));
+    }
+
+    #[test]
+    fn is_same_group_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let p1 = Point::new(
+            Coordinate::Num(FieldElement::new(192, prime)),
+            Coordinate::Num(FieldElement::new(105, prime)),
+            a,
+            b,
+        );
+        let p2 = Point::new(
+            Coordinate::Num(FieldElement::new(17, prime)),
+            Coordinate::Num(FieldElement::new(56, prime)),
+            a,
+            b,
+        );
+        let inf = Point::new(Coordinate::Inf, Coordinate::Inf, a, b);
+        assert!(p1.is_same_group(&p2));
+        assert!(p1.is_same_group(&inf));
+        let other_curve = Point::new(
+            Coordinate::Inf,
+            Coordinate::Inf,
+            a,
+            FieldElement::new(5, prime),
+        );
+        assert!(!p1.is_same_group(&other_curve));
+    }
+
     #[test]
     fn add_test_1() {
         let prime = 223;
@@ -173,6 +335,138 @@ mod tests {
         assert_eq!(&p1 + p1, p2);
     }
 
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn double_ref_combinations_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let x1 = Coordinate::Num(FieldElement::new(192, prime));
+        let y1 = Coordinate::Num(FieldElement::new(105, prime));
+        let p1 = Point::new(x1, y1, a, b);
+        let x2 = Coordinate::Num(FieldElement::new(49, prime));
+        let y2 = Coordinate::Num(FieldElement::new(71, prime));
+        let p2 = Point::new(x2, y2, a, b);
+        assert_eq!(p1 + p1, p2);
+        assert_eq!(&p1 + p1, p2);
+        assert_eq!(p1 + &p1, p2);
+        assert_eq!(&p1 + &p1, p2);
+    }
+
+    #[test]
+    fn subgroup_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let x = Coordinate::Num(FieldElement::new(15, prime));
+        let y = Coordinate::Num(FieldElement::new(86, prime));
+        let g = Point::new(x, y, a, b);
+        let points: Vec<Point> = g.subgroup().collect();
+        assert_eq!(points.len(), 7);
+        assert_eq!(points[0], g);
+        assert_eq!(
+            points[6],
+            Point::new(Coordinate::Inf, Coordinate::Inf, a, b)
+        );
+        for (i, p) in points.iter().enumerate() {
+            assert!(points[i + 1..].iter().all(|q| q != p));
+        }
+    }
+
+    #[test]
+    fn is_inverse_of_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let p = Point::new(
+            Coordinate::Num(FieldElement::new(192, prime)),
+            Coordinate::Num(FieldElement::new(105, prime)),
+            a,
+            b,
+        );
+        let neg_p = Point::new(
+            Coordinate::Num(FieldElement::new(192, prime)),
+            Coordinate::Num(FieldElement::new(prime - 105, prime)),
+            a,
+            b,
+        );
+        let inf = Point::new(Coordinate::Inf, Coordinate::Inf, a, b);
+        assert!(p.is_inverse_of(&neg_p));
+        assert!(neg_p.is_inverse_of(&p));
+        assert!(!p.is_inverse_of(&p));
+        assert!(inf.is_inverse_of(&inf));
+        assert!(!p.is_inverse_of(&inf));
+        assert_eq!(p + neg_p, inf);
+    }
+
+    #[test]
+    fn mul_fe_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let x = Coordinate::Num(FieldElement::new(15, prime));
+        let y = Coordinate::Num(FieldElement::new(86, prime));
+        let g = Point::new(x, y, a, b);
+        // (15, 86) generates a group of order 7
+        let order = 7;
+        assert_eq!(g.mul_fe(&FieldElement::new(3, order)), g + g + g);
+        assert_eq!(
+            g.mul_fe(&FieldElement::new(0, order)),
+            Point::new(Coordinate::Inf, Coordinate::Inf, a, b)
+        );
+        let k = FieldElement::new(5, order);
+        assert_eq!(g.mul_fe(&(k + k)), g.mul_fe(&FieldElement::new(3, order)));
+    }
+
+    #[test]
+    fn negative_scalar_multiplication_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let x = Coordinate::Num(FieldElement::new(47, prime));
+        let y = Coordinate::Num(FieldElement::new(71, prime));
+        let p = Point::new(x, y, a, b);
+        let inf = Point::new(Coordinate::Inf, Coordinate::Inf, a, b);
+        assert_eq!(-3 * p, -(3 * p));
+        assert_eq!(
+            -p,
+            Point::new(x, Coordinate::Num(FieldElement::new(152, prime)), a, b)
+        );
+        assert!(p.is_inverse_of(&-p));
+        assert_eq!(-3 * p + 3 * p, inf);
+        assert_eq!(-21 * p, inf);
+        assert_eq!(-(inf), inf);
+    }
+
+    #[test]
+    fn mul_with_table_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let x = Coordinate::Num(FieldElement::new(47, prime));
+        let y = Coordinate::Num(FieldElement::new(71, prime));
+        let g = Point::new(x, y, a, b);
+        let table = g.precompute(6);
+        assert_eq!(table.len(), 6);
+        assert_eq!(table[0], g);
+        for k in 0..64u64 {
+            assert_eq!(g.mul_with_table(k, &table), k as i64 * g);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Scalar needs 7 bits but the table only covers 6")]
+    fn mul_with_short_table_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let x = Coordinate::Num(FieldElement::new(47, prime));
+        let y = Coordinate::Num(FieldElement::new(71, prime));
+        let g = Point::new(x, y, a, b);
+        let table = g.precompute(6);
+        let _ = g.mul_with_table(64, &table);
+    }
+
     #[test]
     fn scalar_multiplication_test() {
         let prime = 223;
@@ -186,4 +480,66 @@ mod tests {
         let p2 = Point::new(x2, y2, a, b);
         assert_eq!(7 * p1, p2);
     }
+
+    #[test]
+    #[allow(clippy::erasing_op)]
+    fn zero_and_one_scalar_multiplication_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let x1 = Coordinate::Num(FieldElement::new(15, prime));
+        let y1 = Coordinate::Num(FieldElement::new(86, prime));
+        let p1 = Point::new(x1, y1, a, b);
+        let inf = Point::new(Coordinate::Inf, Coordinate::Inf, a, b);
+        assert_eq!(0 * p1, inf);
+        assert_eq!(1 * p1, p1);
+        assert_eq!(1 * inf, inf);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot add points over different fields: F_223 and F_103")]
+    fn add_different_fields_test() {
+        let p1 = Point::new(
+            Coordinate::Num(FieldElement::new(15, 223)),
+            Coordinate::Num(FieldElement::new(86, 223)),
+            FieldElement::new(0, 223),
+            FieldElement::new(7, 223),
+        );
+        let p2 = Point::new(
+            Coordinate::Num(FieldElement::new(1, 103)),
+            Coordinate::Num(FieldElement::new(27, 103)),
+            FieldElement::new(0, 103),
+            FieldElement::new(7, 103),
+        );
+        let _ = p1 + p2;
+    }
+
+    #[test]
+    #[cfg(feature = "debug-checks")]
+    fn checked_scalar_multiplication_test() {
+        let prime = 223;
+        let a = FieldElement::new(0, prime);
+        let b = FieldElement::new(7, prime);
+        let x1 = Coordinate::Num(FieldElement::new(47, prime));
+        let y1 = Coordinate::Num(FieldElement::new(71, prime));
+        let p1 = Point::new(x1, y1, a, b);
+        let x2 = Coordinate::Num(FieldElement::new(47, prime));
+        let y2 = Coordinate::Num(FieldElement::new(152, prime));
+        let p2 = Point::new(x2, y2, a, b);
+        assert_eq!(20 * p1, p2);
+    }
+
+    #[test]
+    #[cfg(feature = "debug-checks")]
+    #[should_panic(expected = "of scalar multiplication is not on the curve")]
+    fn off_curve_scalar_multiplication_test() {
+        let prime = 223;
+        let p = Point {
+            a: FieldElement::new(0, prime),
+            b: FieldElement::new(7, prime),
+            x: Coordinate::Num(FieldElement::new(200, prime)),
+            y: Coordinate::Num(FieldElement::new(119, prime)),
+        };
+        let _ = 3 * p;
+    }
 }
@@ -1,8 +1,9 @@
 use crate::field_element::FieldElement;
+use crate::forward_ref_binop;
 use crate::point::Point;
 use crate::point::Coordinate;
 use lazy_static::lazy_static;
-use std::ops::{Add, Mul};
+use std::ops::{Add, Mul, Neg};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct S256Field {
@@ -27,6 +28,7 @@ impl S256Field {
 const A: i64 = 0;
 const B: i64 = 7;
 
+// Crate-internal until S256Field can hold 256-bit values and S256Point can be built from outside
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct S256Point {
     point: Point,
@@ -58,6 +60,22 @@ impl S256Point {
             }
         }
     }
+
+    pub fn as_point(&self) -> &Point {
+        &self.point
+    }
+}
+
+impl PartialEq<Point> for S256Point {
+    fn eq(&self, other: &Point) -> bool {
+        self.as_point() == other
+    }
+}
+
+impl PartialEq<S256Point> for Point {
+    fn eq(&self, other: &S256Point) -> bool {
+        self == other.as_point()
+    }
 }
 
 impl Add for S256Point {
@@ -67,14 +85,33 @@ impl Add for S256Point {
         S256Point { point: self.point + other.point }
     }
 }
+forward_ref_binop! { impl Add, add for S256Point }
+
+impl Neg for S256Point {
+    type Output = S256Point;
+
+    fn neg(self) -> S256Point {
+        S256Point { point: -self.point }
+    }
+}
 
 impl Mul<S256Point> for i64 {
     type Output = S256Point;
 
     fn mul(self, other: S256Point) -> S256Point {
-        S256Point { point: (self % *N) * other.point }
+        // Point multiplication already short-circuits these, and skipping the reduction
+        // avoids touching N
+        if self == 0 || self == 1 {
+            return S256Point {
+                point: self * other.point,
+            };
+        }
+        S256Point {
+            point: (self % *N) * other.point,
+        }
     }
 }
+forward_ref_binop! { impl Mul, mul for i64, S256Point }
 
 #[cfg(test)]
 mod tests {
@@ -86,4 +123,67 @@ mod tests {
         let p2 = S256Point::inf();
         assert_eq!(*N * p1, p2);
     }
+
+    // S256Field cannot be built on the i64 backend yet, so wrap a point on a small curve
+    fn point(x: i64, y: i64) -> S256Point {
+        let prime = 223;
+        S256Point {
+            point: Point {
+                x: Coordinate::Num(FieldElement::new(x, prime)),
+                y: Coordinate::Num(FieldElement::new(y, prime)),
+                a: FieldElement::new(0, prime),
+                b: FieldElement::new(7, prime),
+            },
+        }
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn add_ref_test() {
+        let p1 = point(170, 142);
+        let p2 = point(60, 139);
+        let p3 = point(220, 181);
+        assert_eq!(&p1 + &p2, p3);
+        assert_eq!(&p1 + p2, p3);
+        assert_eq!(p1 + &p2, p3);
+        assert_eq!(p1 + p2, p3);
+    }
+
+    #[test]
+    fn point_eq_test() {
+        let p = point(170, 142);
+        assert_eq!(p, p.point);
+        assert_eq!(p.point, p);
+        assert_eq!(p.as_point(), &p.point);
+        assert_ne!(p, point(60, 139).point);
+    }
+
+    #[test]
+    #[allow(clippy::erasing_op)]
+    fn zero_and_one_mul_test() {
+        let p = point(170, 142);
+        assert_eq!(
+            0 * p,
+            Point {
+                x: Coordinate::Inf,
+                y: Coordinate::Inf,
+                ..p.point
+            }
+        );
+        assert_eq!(1 * p, p);
+    }
+
+    #[test]
+    fn neg_test() {
+        let p = point(170, 142);
+        assert_eq!(-p, point(170, 81));
+        assert_eq!(
+            -p + p,
+            Point {
+                x: Coordinate::Inf,
+                y: Coordinate::Inf,
+                ..p.point
+            }
+        );
+    }
 }
@@ -0,0 +1,334 @@
+use crate::op::{self, Command};
+
+const OP_0: u8 = 0x00;
+const OP_RETURN: u8 = 0x6a;
+const OP_DUP: u8 = 0x76;
+const OP_EQUAL: u8 = 0x87;
+const OP_EQUALVERIFY: u8 = 0x88;
+const OP_HASH160: u8 = 0xa9;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKMULTISIG: u8 = 0xae;
+
+// The value pushed by OP_1 through OP_16
+fn small_int(op_code: u8) -> Option<usize> {
+    match op_code {
+        0x51..=0x60 => Some((op_code - 0x50) as usize),
+        _ => None,
+    }
+}
+
+// OP_m <pubkey>... OP_n with 1 <= m <= n and n SEC-sized keys
+fn is_multisig(m: u8, keys: &[Command], n: u8) -> bool {
+    match (small_int(m), small_int(n)) {
+        (Some(m), Some(n)) => {
+            m <= n
+                && keys.len() == n
+                && keys.iter().all(|key| match key {
+                    Command::Element(sec) => sec.len() == 33 || sec.len() == 65,
+                    Command::Op(_) => false,
+                })
+        }
+        _ => false,
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Script {
+    cmds: Vec<Command>,
+}
+
+impl Script {
+    pub fn new(cmds: Vec<Command>) -> Self {
+        Script { cmds }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Command> {
+        self.cmds.iter()
+    }
+
+    pub fn push(&mut self, cmd: Command) {
+        self.cmds.push(cmd);
+    }
+
+    // OP_0 <32-byte sha256 of the witness script>
+    pub fn p2wsh_script(witness_script_hash: &[u8; 32]) -> Script {
+        Script::new(vec![
+            Command::Op(OP_0),
+            Command::Element(witness_script_hash.to_vec()),
+        ])
+    }
+
+    // The (version, program) of a segwit output: OP_0 through OP_16 followed by a 2 to 40 byte push
+    pub fn witness_program(&self) -> Option<(u8, Vec<u8>)> {
+        match self.cmds.as_slice() {
+            [Command::Op(op_code), Command::Element(program)]
+                if (2..=40).contains(&program.len()) =>
+            {
+                let version = match *op_code {
+                    OP_0 => 0,
+                    op_code => small_int(op_code)? as u8,
+                };
+                Some((version, program.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    // Appends other's commands, e.g. a script_pubkey after the script_sig that unlocks it
+    pub fn combine(mut self, other: Script) -> Script {
+        self.cmds.extend(other.cmds);
+        self
+    }
+
+    // The standard output template these commands follow, or "nonstandard"
+    pub fn describe(&self) -> &'static str {
+        use Command::{Element, Op};
+        match self.cmds.as_slice() {
+            [Op(OP_DUP), Op(OP_HASH160), Element(hash), Op(OP_EQUALVERIFY), Op(OP_CHECKSIG)]
+                if hash.len() == 20 =>
+            {
+                "p2pkh"
+            }
+            [Op(OP_HASH160), Element(hash), Op(OP_EQUAL)] if hash.len() == 20 => "p2sh",
+            [Op(OP_0), Element(hash)] if hash.len() == 20 => "p2wpkh",
+            [Op(OP_0), Element(hash)] if hash.len() == 32 => "p2wsh",
+            [Op(OP_RETURN), ..] => "op_return",
+            [Op(m), keys @ .., Op(n), Op(OP_CHECKMULTISIG)] if is_multisig(*m, keys, *n) => "p2ms",
+            _ => "nonstandard",
+        }
+    }
+
+    // The pubkey hashes (p2pkh, p2wpkh) or pubkeys (bare multisig) a spender must sign for;
+    // empty for every other pattern
+    pub fn required_signers(&self) -> Vec<Vec<u8>> {
+        use Command::{Element, Op};
+        match self.cmds.as_slice() {
+            [Op(OP_DUP), Op(OP_HASH160), Element(hash), Op(OP_EQUALVERIFY), Op(OP_CHECKSIG)]
+            | [Op(OP_0), Element(hash)]
+                if hash.len() == 20 =>
+            {
+                vec![hash.clone()]
+            }
+            [Op(m), keys @ .., Op(n), Op(OP_CHECKMULTISIG)] if is_multisig(*m, keys, *n) => keys
+                .iter()
+                .filter_map(|key| match key {
+                    Element(sec) => Some(sec.clone()),
+                    Op(_) => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // Runs self as the script_sig followed by script_pubkey
+    pub fn evaluate(&self, script_pubkey: &Script) -> bool {
+        let combined = self.clone().combine(script_pubkey.clone());
+        op::evaluate(&combined.cmds)
+    }
+}
+
+impl<'a> IntoIterator for &'a Script {
+    type Item = &'a Command;
+    type IntoIter = std::slice::Iter<'a, Command>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::op::encode_num;
+
+    fn num(n: i64) -> Command {
+        Command::Element(encode_num(n))
+    }
+
+    #[test]
+    fn combine_test() {
+        let script_sig = Script::new(vec![num(2)]);
+        let script_pubkey = Script::new(vec![num(3), Command::Op(0x93)]);
+        let combined = script_sig.combine(script_pubkey);
+        assert_eq!(
+            combined,
+            Script::new(vec![num(2), num(3), Command::Op(0x93)])
+        );
+    }
+
+    #[test]
+    fn iter_test() {
+        let mut script = Script::default();
+        script.push(num(1));
+        script.push(Command::Op(0x69));
+        assert_eq!(
+            script.iter().collect::<Vec<_>>(),
+            vec![&num(1), &Command::Op(0x69)]
+        );
+
+        let mut ops = 0;
+        for cmd in &script {
+            if let Command::Op(_) = cmd {
+                ops += 1;
+            }
+        }
+        assert_eq!(ops, 1);
+    }
+
+    #[test]
+    fn evaluate_test() {
+        // 2 | 3 OP_ADD 5 OP_NUMEQUAL
+        let script_pubkey = Script::new(vec![num(3), Command::Op(0x93), num(5), Command::Op(0x9c)]);
+        assert!(Script::new(vec![num(2)]).evaluate(&script_pubkey));
+        assert!(!Script::new(vec![num(1)]).evaluate(&script_pubkey));
+        assert!(!Script::default().evaluate(&script_pubkey));
+    }
+
+    fn element(len: usize) -> Command {
+        Command::Element(vec![0x02; len])
+    }
+
+    #[test]
+    fn describe_test() {
+        let p2pkh = Script::new(vec![
+            Command::Op(OP_DUP),
+            Command::Op(OP_HASH160),
+            element(20),
+            Command::Op(OP_EQUALVERIFY),
+            Command::Op(OP_CHECKSIG),
+        ]);
+        assert_eq!(p2pkh.describe(), "p2pkh");
+
+        let p2sh = Script::new(vec![
+            Command::Op(OP_HASH160),
+            element(20),
+            Command::Op(OP_EQUAL),
+        ]);
+        assert_eq!(p2sh.describe(), "p2sh");
+
+        let p2wpkh = Script::new(vec![Command::Op(OP_0), element(20)]);
+        assert_eq!(p2wpkh.describe(), "p2wpkh");
+
+        // OP_2 <compressed> <compressed> <uncompressed> OP_3 OP_CHECKMULTISIG
+        let p2ms = Script::new(vec![
+            Command::Op(0x52),
+            element(33),
+            element(33),
+            element(65),
+            Command::Op(0x53),
+            Command::Op(OP_CHECKMULTISIG),
+        ]);
+        assert_eq!(p2ms.describe(), "p2ms");
+
+        let p2wsh = Script::p2wsh_script(&[0x02; 32]);
+        assert_eq!(p2wsh.describe(), "p2wsh");
+
+        let op_return = Script::new(vec![Command::Op(OP_RETURN), element(4)]);
+        assert_eq!(op_return.describe(), "op_return");
+    }
+
+    #[test]
+    fn describe_nonstandard_test() {
+        assert_eq!(Script::default().describe(), "nonstandard");
+        // A 19-byte hash is not a p2pkh output
+        let short_hash = Script::new(vec![
+            Command::Op(OP_DUP),
+            Command::Op(OP_HASH160),
+            element(19),
+            Command::Op(OP_EQUALVERIFY),
+            Command::Op(OP_CHECKSIG),
+        ]);
+        assert_eq!(short_hash.describe(), "nonstandard");
+        // OP_3 of OP_2 keys, and a key count that does not match n
+        let too_many_required = Script::new(vec![
+            Command::Op(0x53),
+            element(33),
+            element(33),
+            Command::Op(0x52),
+            Command::Op(OP_CHECKMULTISIG),
+        ]);
+        assert_eq!(too_many_required.describe(), "nonstandard");
+        let wrong_count = Script::new(vec![
+            Command::Op(0x51),
+            element(33),
+            Command::Op(0x52),
+            Command::Op(OP_CHECKMULTISIG),
+        ]);
+        assert_eq!(wrong_count.describe(), "nonstandard");
+    }
+
+    #[test]
+    fn p2wsh_script_test() {
+        let hash = [0xab; 32];
+        let script = Script::p2wsh_script(&hash);
+        assert_eq!(
+            script,
+            Script::new(vec![Command::Op(OP_0), Command::Element(hash.to_vec())])
+        );
+        assert_eq!(script.witness_program(), Some((0, hash.to_vec())));
+    }
+
+    #[test]
+    fn witness_program_test() {
+        let p2wpkh = Script::new(vec![Command::Op(OP_0), element(20)]);
+        assert_eq!(p2wpkh.witness_program(), Some((0, vec![0x02; 20])));
+        // OP_1 <32 bytes> is a version 1 (taproot) output
+        let v1 = Script::new(vec![Command::Op(0x51), element(32)]);
+        assert_eq!(v1.witness_program(), Some((1, vec![0x02; 32])));
+        let v16 = Script::new(vec![Command::Op(0x60), element(40)]);
+        assert_eq!(v16.witness_program(), Some((16, vec![0x02; 40])));
+
+        assert_eq!(
+            Script::new(vec![Command::Op(OP_0), element(1)]).witness_program(),
+            None
+        );
+        assert_eq!(
+            Script::new(vec![Command::Op(OP_0), element(41)]).witness_program(),
+            None
+        );
+        assert_eq!(
+            Script::new(vec![Command::Op(OP_DUP), element(20)]).witness_program(),
+            None
+        );
+        assert_eq!(Script::default().witness_program(), None);
+    }
+
+    #[test]
+    fn required_signers_p2pkh_test() {
+        let hash = vec![0xab; 20];
+        let p2pkh = Script::new(vec![
+            Command::Op(OP_DUP),
+            Command::Op(OP_HASH160),
+            Command::Element(hash.clone()),
+            Command::Op(OP_EQUALVERIFY),
+            Command::Op(OP_CHECKSIG),
+        ]);
+        assert_eq!(p2pkh.required_signers(), vec![hash.clone()]);
+        let p2wpkh = Script::new(vec![Command::Op(OP_0), Command::Element(hash.clone())]);
+        assert_eq!(p2wpkh.required_signers(), vec![hash]);
+    }
+
+    #[test]
+    fn required_signers_multisig_test() {
+        let keys = vec![vec![0x02; 33], vec![0x03; 33], vec![0x04; 65]];
+        let mut p2ms = Script::new(vec![Command::Op(0x52)]);
+        for key in &keys {
+            p2ms.push(Command::Element(key.clone()));
+        }
+        p2ms.push(Command::Op(0x53));
+        p2ms.push(Command::Op(OP_CHECKMULTISIG));
+        assert_eq!(p2ms.required_signers(), keys);
+    }
+
+    #[test]
+    fn required_signers_other_test() {
+        let p2sh = Script::new(vec![
+            Command::Op(OP_HASH160),
+            element(20),
+            Command::Op(OP_EQUAL),
+        ]);
+        assert!(p2sh.required_signers().is_empty());
+        assert!(Script::p2wsh_script(&[0; 32]).required_signers().is_empty());
+        assert!(Script::default().required_signers().is_empty());
+    }
+}
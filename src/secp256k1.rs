@@ -0,0 +1,106 @@
+use bigint::U256;
+use std::sync::OnceLock;
+
+fn from_hex(s: &str) -> U256 {
+    let bytes: Vec<u8> = (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).expect("hardcoded value should be hex"))
+        .collect();
+    U256::from_big_endian(&bytes)
+}
+
+// A hex constant parsed on first use and cached from then on
+struct HexConstant {
+    hex: &'static str,
+    value: OnceLock<U256>,
+}
+
+impl HexConstant {
+    const fn new(hex: &'static str) -> Self {
+        HexConstant {
+            hex,
+            value: OnceLock::new(),
+        }
+    }
+
+    fn get(&self) -> U256 {
+        self.get_with(from_hex)
+    }
+
+    fn get_with(&self, parse: impl FnOnce(&str) -> U256) -> U256 {
+        *self.value.get_or_init(|| parse(self.hex))
+    }
+}
+
+static P: HexConstant =
+    HexConstant::new("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f");
+static N: HexConstant =
+    HexConstant::new("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+static GX: HexConstant =
+    HexConstant::new("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+static GY: HexConstant =
+    HexConstant::new("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8");
+
+pub const A: u64 = 0;
+pub const B: u64 = 7;
+
+// The prime of the field the curve is defined over
+pub fn p() -> U256 {
+    P.get()
+}
+
+// The order of the group generated by the generator point
+pub fn n() -> U256 {
+    N.get()
+}
+
+// The (x, y) coordinates of the generator point G
+pub fn generator() -> (U256, U256) {
+    (GX.get(), GY.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigint::U512;
+    use std::cell::Cell;
+
+    fn mul_mod(a: U256, b: U256) -> U256 {
+        (a.full_mul(b) % U512::from(p())).into()
+    }
+
+    #[test]
+    fn p_test() {
+        let two = U256::from(2u64);
+        let expected = U256::max_value() - two.pow(U256::from(32u64)) - U256::from(976u64);
+        assert_eq!(p(), expected);
+        // 2^256 itself does not fit, so check p + 2^32 + 977 wraps around to zero
+        let (sum, overflow) = p().overflowing_add(two.pow(U256::from(32u64)) + U256::from(977u64));
+        assert!(overflow);
+        assert!(sum.is_zero());
+    }
+
+    #[test]
+    fn generator_on_curve_test() {
+        let (x, y) = generator();
+        let lhs = mul_mod(y, y);
+        let rhs = (U512::from(mul_mod(mul_mod(x, x), x)) + U512::from(B)) % U512::from(p());
+        assert_eq!(lhs, U256::from(rhs));
+    }
+
+    #[test]
+    fn constant_parsed_once_test() {
+        let constant = HexConstant::new("07");
+        let parses = Cell::new(0);
+        let counting_parse = |hex: &str| {
+            parses.set(parses.get() + 1);
+            from_hex(hex)
+        };
+        for _ in 0..100 {
+            assert_eq!(constant.get_with(counting_parse), U256::from(7u64));
+        }
+        assert_eq!(parses.get(), 1);
+        assert_eq!(constant.get(), U256::from(7u64));
+        assert_eq!(parses.get(), 1);
+    }
+}
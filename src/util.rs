@@ -0,0 +1,195 @@
+use bigint::U256;
+use std::io::{self, Read, Write};
+
+// Packs bits LSB-first within each byte, padding the last byte with zeros
+pub fn bit_field_to_bytes(bit_field: &[bool]) -> Vec<u8> {
+    let mut result = vec![0u8; bit_field.len().div_ceil(8)];
+    for (i, bit) in bit_field.iter().enumerate() {
+        if *bit {
+            result[i / 8] |= 1 << (i % 8);
+        }
+    }
+    result
+}
+
+pub fn bytes_to_bit_field(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| byte & (1 << i) != 0))
+        .collect()
+}
+
+// Reads exactly N bytes, reporting how many were expected if the input runs out
+pub fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("expected {} bytes but the input ended early", N),
+        ),
+        _ => e,
+    })?;
+    Ok(buf)
+}
+
+pub fn read_u16_le(reader: &mut impl Read) -> io::Result<u16> {
+    read_array(reader).map(u16::from_le_bytes)
+}
+
+pub fn read_u32_le(reader: &mut impl Read) -> io::Result<u32> {
+    read_array(reader).map(u32::from_le_bytes)
+}
+
+pub fn read_u64_le(reader: &mut impl Read) -> io::Result<u64> {
+    read_array(reader).map(u64::from_le_bytes)
+}
+
+pub fn write_u16_le(writer: &mut impl Write, value: u16) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub fn write_u32_le(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub fn write_u64_le(writer: &mut impl Write, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+// bits is the block header field as serialized: a little-endian coefficient followed by an exponent byte
+pub fn bits_to_target(bits: [u8; 4]) -> U256 {
+    let exponent = bits[3] as usize;
+    let coefficient = U256::from(u32::from_le_bytes([bits[0], bits[1], bits[2], 0]));
+    if exponent >= 3 {
+        coefficient << (8 * (exponent - 3))
+    } else {
+        coefficient >> (8 * (3 - exponent))
+    }
+}
+
+// The target as 64 zero-padded hex digits
+pub fn target_hex(bits: [u8; 4]) -> String {
+    let mut bytes = [0u8; 32];
+    bits_to_target(bits).to_big_endian(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// How much harder the target is than the lowest difficulty target, 0xffff * 256^(0x1d - 3)
+pub fn bits_to_difficulty(bits: [u8; 4]) -> f64 {
+    let exponent = bits[3] as i32;
+    let coefficient = u32::from_le_bytes([bits[0], bits[1], bits[2], 0]) as f64;
+    let target = coefficient * 256f64.powi(exponent - 3);
+    0xffff as f64 * 256f64.powi(0x1d - 3) / target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_field_to_bytes_test() {
+        let mut bits = vec![false; 16];
+        bits[0] = true;
+        bits[9] = true;
+        bits[15] = true;
+        assert_eq!(bit_field_to_bytes(&bits), vec![0x01, 0x82]);
+        assert_eq!(bit_field_to_bytes(&[true, true, false, true]), vec![0x0b]);
+    }
+
+    #[test]
+    fn bytes_to_bit_field_test() {
+        let bits = bytes_to_bit_field(&[0x0b, 0x80]);
+        assert_eq!(bits.len(), 16);
+        assert_eq!(&bits[..4], &[true, true, false, true]);
+        assert!(bits[15]);
+        assert_eq!(bits.iter().filter(|bit| **bit).count(), 4);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let bytes = vec![0x00, 0xff, 0x5a, 0x01, 0x80];
+        assert_eq!(bit_field_to_bytes(&bytes_to_bit_field(&bytes)), bytes);
+    }
+
+    #[test]
+    fn read_le_test() {
+        let bytes = [
+            0x01, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0x00, 0xe1, 0xf5, 0x05,
+        ];
+        let mut reader = &bytes[..];
+        assert_eq!(read_u32_le(&mut reader).unwrap(), 1);
+        assert_eq!(read_u16_le(&mut reader).unwrap(), 0xffff);
+        assert_eq!(read_u16_le(&mut reader).unwrap(), 0xffff);
+        assert_eq!(read_u32_le(&mut reader).unwrap(), 100_000_000);
+        assert!(reader.is_empty());
+
+        let bytes = [0x00, 0xe1, 0xf5, 0x05, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(read_u64_le(&mut &bytes[..]).unwrap(), 100_000_000);
+    }
+
+    #[test]
+    fn read_le_short_input_test() {
+        let bytes = [0x01, 0x02, 0x03];
+        let err = read_u32_le(&mut &bytes[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn write_le_test() {
+        let mut buf = Vec::new();
+        write_u16_le(&mut buf, 0x0102).unwrap();
+        write_u32_le(&mut buf, 1).unwrap();
+        write_u64_le(&mut buf, 100_000_000).unwrap();
+        assert_eq!(
+            buf,
+            vec![
+                0x02, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0xe1, 0xf5, 0x05, 0x00, 0x00, 0x00, 0x00
+            ]
+        );
+        assert_eq!(read_u64_le(&mut &buf[6..]).unwrap(), 100_000_000);
+    }
+
+    #[test]
+    fn read_array_test() {
+        let bytes: Vec<u8> = (0..40).collect();
+        let mut reader = &bytes[..];
+        let hash: [u8; 32] = read_array(&mut reader).unwrap();
+        assert_eq!(hash.to_vec(), (0..32).collect::<Vec<u8>>());
+        assert_eq!(reader.len(), 8);
+    }
+
+    #[test]
+    fn read_array_short_input_test() {
+        let bytes = [0u8; 31];
+        let err = read_array::<32>(&mut &bytes[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "expected 32 bytes but the input ended early"
+        );
+    }
+
+    #[test]
+    fn genesis_bits_test() {
+        let bits = [0xff, 0xff, 0x00, 0x1d];
+        assert_eq!(
+            target_hex(bits),
+            "00000000ffff0000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(bits_to_difficulty(bits), 1.0);
+    }
+
+    #[test]
+    fn bits_to_target_test() {
+        let bits = [0xe9, 0x3c, 0x01, 0x18];
+        assert_eq!(
+            target_hex(bits),
+            "0000000000000000013ce9000000000000000000000000000000000000000000"
+        );
+        assert!((bits_to_difficulty(bits) - 888171856257.3206).abs() < 1e-3);
+        assert_eq!(
+            bits_to_target([0x12, 0x34, 0x56, 0x02]),
+            U256::from(0x5634u64)
+        );
+    }
+}
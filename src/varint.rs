@@ -0,0 +1,119 @@
+use crate::util::{read_array, read_u16_le, read_u32_le, read_u64_le};
+use std::fmt;
+use std::io::{self, Read};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VarintError {
+    // The input ended before the full integer; carries how many bytes were needed
+    Truncated(usize),
+}
+
+impl fmt::Display for VarintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VarintError::Truncated(needed) => {
+                write!(f, "varint needs {} bytes but the input is shorter", needed)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VarintError {}
+
+pub fn encode(n: u64) -> Vec<u8> {
+    if n < 0xfd {
+        vec![n as u8]
+    } else if n <= 0xffff {
+        let mut result = vec![0xfd];
+        result.extend_from_slice(&(n as u16).to_le_bytes());
+        result
+    } else if n <= 0xffff_ffff {
+        let mut result = vec![0xfe];
+        result.extend_from_slice(&(n as u32).to_le_bytes());
+        result
+    } else {
+        let mut result = vec![0xff];
+        result.extend_from_slice(&n.to_le_bytes());
+        result
+    }
+}
+
+pub fn decode(reader: &mut impl Read) -> io::Result<u64> {
+    let [prefix] = read_array(reader)?;
+    match prefix {
+        0xfd => read_u16_le(reader).map(u64::from),
+        0xfe => read_u32_le(reader).map(u64::from),
+        0xff => read_u64_le(reader),
+        n => Ok(u64::from(n)),
+    }
+}
+
+// Like decode, but also returns how many bytes of the slice the integer took up
+pub fn decode_slice(bytes: &[u8]) -> Result<(u64, usize), VarintError> {
+    let len = match bytes.first() {
+        None => return Err(VarintError::Truncated(1)),
+        Some(0xfd) => 3,
+        Some(0xfe) => 5,
+        Some(0xff) => 9,
+        Some(_) => 1,
+    };
+    if bytes.len() < len {
+        return Err(VarintError::Truncated(len));
+    }
+
+    let mut buf = [0u8; 8];
+    if len == 1 {
+        buf[0] = bytes[0];
+    } else {
+        buf[..len - 1].copy_from_slice(&bytes[1..len]);
+    }
+    Ok((u64::from_le_bytes(buf), len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_test() {
+        assert_eq!(encode(0x64), vec![0x64]);
+        assert_eq!(encode(0xfd), vec![0xfd, 0xfd, 0x00]);
+        assert_eq!(encode(0x1_0000), vec![0xfe, 0x00, 0x00, 0x01, 0x00]);
+        assert_eq!(
+            encode(0x1_0000_0000),
+            vec![0xff, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn decode_test() {
+        for n in [0, 0xfc, 0xfd, 0xffff, 0x1_0000, 0xffff_ffff, u64::MAX] {
+            assert_eq!(decode(&mut &encode(n)[..]).unwrap(), n);
+        }
+        assert!(decode(&mut &[0xfe, 0x00][..]).is_err());
+    }
+
+    #[test]
+    fn decode_slice_test() {
+        assert_eq!(decode_slice(&[0x64, 0xaa]), Ok((0x64, 1)));
+        assert_eq!(decode_slice(&[0xfd, 0x26, 0x02, 0xaa]), Ok((550, 3)));
+        assert_eq!(
+            decode_slice(&[0xfe, 0x00, 0x00, 0x01, 0x00]),
+            Ok((0x1_0000, 5))
+        );
+        assert_eq!(
+            decode_slice(&[0xff, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0xaa]),
+            Ok((0x1_0000_0000, 9))
+        );
+    }
+
+    #[test]
+    fn decode_slice_truncated_test() {
+        assert_eq!(decode_slice(&[]), Err(VarintError::Truncated(1)));
+        assert_eq!(decode_slice(&[0xfd, 0x26]), Err(VarintError::Truncated(3)));
+        assert_eq!(
+            decode_slice(&[0xff, 0x00, 0x00]),
+            Err(VarintError::Truncated(9))
+        );
+    }
+}